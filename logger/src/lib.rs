@@ -4,14 +4,16 @@ use colored::Colorize;
 #[derive(Debug)]
 pub struct Logger {
     verbose: bool,
-    colored: bool
+    colored: bool,
+    stderr: bool
 }
 
 impl Default for Logger {
     fn default() -> Self {
         Logger {
             verbose: false,
-            colored: false
+            colored: false,
+            stderr: false
         }
     }
 }
@@ -27,6 +29,12 @@ impl Logger {
         self
     }
 
+    // keeps stdout free for program output
+    pub fn stderr(mut self, stderr: bool) -> Self {
+        self.stderr = stderr;
+        self
+    }
+
     pub fn get_max_log_level(&self) -> LevelFilter {
         if self.verbose {
             LevelFilter::Debug
@@ -55,22 +63,28 @@ impl Log for Logger {
     fn flush(&self) {}
 
     fn log(&self, record: &log::Record) {
-        if self.colored {
+        let line: String = if self.colored {
             match record.level() {
-                Level::Debug => println!("{} {}", "[DBG]"  .cyan(),    record.args()),
-                Level::Info =>  println!("{} {}", "[INF]"  .green(),   record.args()),
-                Level::Warn =>  println!("{} {}", "[WRN]"  .yellow(),  record.args()),
-                Level::Trace => println!("{} {}", "[TRC]"  .blue(),    record.args()),
-                Level::Error => println!("{} {}", "[ERR]"  .red(),     record.args())
+                Level::Debug => format!("{} {}", "[DBG]"  .cyan(),    record.args()),
+                Level::Info =>  format!("{} {}", "[INF]"  .green(),   record.args()),
+                Level::Warn =>  format!("{} {}", "[WRN]"  .yellow(),  record.args()),
+                Level::Trace => format!("{} {}", "[TRC]"  .blue(),    record.args()),
+                Level::Error => format!("{} {}", "[ERR]"  .red(),     record.args())
             }
         } else {
             match record.level() {
-                Level::Debug => println!("{} {}", "[DBG]", record.args()),
-                Level::Info =>  println!("{} {}", "[INF]", record.args()),
-                Level::Warn =>  println!("{} {}", "[WRN]", record.args()),
-                Level::Trace => println!("{} {}", "[TRC]", record.args()),
-                Level::Error => println!("{} {}", "[ERR]", record.args())
+                Level::Debug => format!("{} {}", "[DBG]", record.args()),
+                Level::Info =>  format!("{} {}", "[INF]", record.args()),
+                Level::Warn =>  format!("{} {}", "[WRN]", record.args()),
+                Level::Trace => format!("{} {}", "[TRC]", record.args()),
+                Level::Error => format!("{} {}", "[ERR]", record.args())
             }
+        };
+
+        if self.stderr {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
         }
     }
 }
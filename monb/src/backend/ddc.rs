@@ -0,0 +1,119 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::debug;
+
+use crate::backend::{drm, max_for, BackendDevice, BrightnessBackend, DeviceError};
+use crate::ddc::Ddc;
+use crate::edid::Edid;
+use crate::ddc::packet::VcpValue;
+use crate::ddc::transport::I2cDevTransport;
+use crate::feature::Feature;
+
+const DEV_ROOT: &'static str = "/dev";
+
+/// Backend talking DDC/CI directly to external monitors through `/dev/i2c-*`.
+/// Buses are found through the `ddc` link of every connected DRM connector.
+#[derive(Debug)]
+pub struct DdcBackend {
+    raw: bool
+}
+
+#[derive(Debug)]
+pub struct DdcDevice {
+    connector: String,
+    connector_path: PathBuf,
+    ddc: Mutex<Ddc<I2cDevTransport>>,
+    raw: bool
+}
+
+impl DdcBackend {
+    pub fn new(raw: bool) -> Self {
+        Self { raw }
+    }
+}
+
+// (`DP-1`, `/sys/class/drm/card0-DP-1`, `/dev/i2c-5`)
+fn connected_ddc_buses() -> Vec<(String, PathBuf, PathBuf)> {
+    drm::connected_connectors().into_iter()
+        .filter_map(|(connector, path)| {
+            let bus: PathBuf = fs::read_link(path.join("ddc")).ok()?;
+            let bus_name = bus.file_name()?.to_string_lossy().into_owned();
+
+            Some((connector, path, Path::new(DEV_ROOT).join(bus_name)))
+        })
+        .collect()
+}
+
+impl BrightnessBackend for DdcBackend {
+    fn name(&self) -> &'static str {
+        "ddc"
+    }
+
+    fn enumerate(&self) -> Vec<Result<Box<dyn BackendDevice>, String>> {
+        connected_ddc_buses().into_iter()
+            .map(|(connector, connector_path, bus)| {
+                debug!("Probing DDC/CI on `{}` ({})", bus.display(), connector);
+
+                let mut ddc: Ddc<I2cDevTransport> = Ddc::new(I2cDevTransport::open(&bus)?);
+                ddc.get_vcp(Feature::Brightness.vcp_code()).map_err(|err| format!("{} - {}", connector, err))?;
+
+                Ok(Box::new(DdcDevice { connector, connector_path, ddc: Mutex::new(ddc), raw: self.raw }) as Box<dyn BackendDevice>)
+            })
+            .collect()
+    }
+}
+
+impl DdcDevice {
+    fn get_vcp(&self, code: u8) -> Result<VcpValue, String> {
+        self.ddc.lock().map_err(|err| err.to_string())?.get_vcp(code)
+    }
+}
+
+impl BackendDevice for DdcDevice {
+    fn get(&self, feature: Feature) -> Result<u32, String> {
+        let value: VcpValue = self.get_vcp(feature.vcp_code())?;
+        if self.raw || !feature.is_continuous() || value.max == 0 {
+            return Ok(value.current as u32);
+        }
+
+        return Ok((value.current as u32 * 100 + value.max as u32 / 2) / value.max as u32);
+    }
+
+    fn set(&self, feature: Feature, value: u32) -> Result<(), DeviceError> {
+        let raw_value: u32 = if feature.is_continuous() {
+            let max: u32 = self.get_vcp(feature.vcp_code())?.max as u32;
+            if self.raw { value.min(max) } else { (value.min(100) * max + 50) / 100 }
+        } else {
+            value.min(u16::MAX as u32)
+        };
+
+        let mut ddc = self.ddc.lock().map_err(|err| err.to_string())?;
+        return Ok(ddc.set_vcp(feature.vcp_code(), raw_value as u16)?);
+    }
+
+    fn describe(&self, index: usize) -> String {
+        format!("Monitor #{} ({})", index, self.connector)
+    }
+
+    fn id(&self) -> String {
+        self.connector.clone()
+    }
+
+    fn connector(&self) -> Option<String> {
+        Some(self.connector.clone())
+    }
+
+    fn edid(&self) -> Result<Option<Edid>, String> {
+        drm::edid(&self.connector_path)
+    }
+
+    fn max(&self, feature: Feature) -> u32 {
+        if self.raw && feature.is_continuous() {
+            return self.get_vcp(feature.vcp_code()).map(|value| value.max as u32).unwrap_or(100);
+        }
+
+        max_for(feature)
+    }
+}
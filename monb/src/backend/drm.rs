@@ -0,0 +1,75 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+use crate::edid::Edid;
+
+pub const DRM_ROOT_ENV: &'static str = "MONB_DRM_ROOT";
+pub const DRM_ROOT_DEFAULT: &'static str = "/sys/class/drm";
+
+/// Root of the DRM connectors, can be moved with `MONB_DRM_ROOT`, e.g. to a fake directory tree.
+pub fn root() -> PathBuf {
+    match std::env::var_os(DRM_ROOT_ENV) {
+        Some(r) => PathBuf::from(r),
+        None => PathBuf::from(DRM_ROOT_DEFAULT)
+    }
+}
+
+/// `card0-DP-1` -> `DP-1`, `None` for entries that aren't connectors (`card0`, `version`, ...)
+pub fn connector_name(file_name: &str) -> Option<String> {
+    match file_name.split_once('-') {
+        Some((card, connector)) if card.starts_with("card") => Some(connector.to_string()),
+        _ => None
+    }
+}
+
+/// Connected connectors sorted by name, as (`DP-1`, `/sys/class/drm/card0-DP-1`) pairs.
+pub fn connected_connectors() -> Vec<(String, PathBuf)> {
+    let root: PathBuf = root();
+    let entries = match fs::read_dir(&root) {
+        Ok(e) => e,
+        Err(err) => {
+            debug!("Failed to read `{}` ({})", root.display(), err);
+            return vec![];
+        }
+    };
+
+    let mut connectors: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let connector: String = connector_name(&entry.file_name().to_string_lossy())?;
+            let status: String = fs::read_to_string(entry.path().join("status")).ok()?;
+
+            match status.trim() {
+                "connected" => Some((connector, entry.path())),
+                _ => None
+            }
+        })
+        .collect();
+
+    connectors.sort();
+    return connectors;
+}
+
+/// Connector driving a backlight device, backlights of built-in panels link to it through `device`.
+pub fn backlight_connector(backlight: &Path) -> Option<(String, PathBuf)> {
+    let device: PathBuf = fs::read_link(backlight.join("device")).ok()?;
+    let connector: String = connector_name(&device.file_name()?.to_string_lossy())?;
+
+    let path: PathBuf = root().join(device.file_name()?);
+    return Some((connector, path));
+}
+
+/// EDID of the display on a connector, `None` if the connector doesn't expose one.
+pub fn edid(connector_path: &Path) -> Result<Option<Edid>, String> {
+    // sysfs reports a size of 0 for every attribute, so the file has to be read to know if it's empty
+    let path: PathBuf = connector_path.join("edid");
+    match fs::read(&path) {
+        Ok(bytes) if bytes.is_empty() => Ok(None),
+        Ok(bytes) => Edid::parse(&bytes).map(Some).map_err(|err| format!("{} (`{}`)", err, path.display())),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(format!("Failed to read `{}` ({})", path.display(), err))
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{max_for, unsupported_feature, BackendDevice, BrightnessBackend, DeviceError};
+use crate::ddc::Ddc;
+use crate::ddc::transport::LoopbackTransport;
+use crate::edid::Edid;
+use crate::feature::Feature;
+
+pub const FAKE_FILE_ENV: &'static str = "MONB_FAKE_FILE";
+
+// Example description file:
+// {
+//     "monitors": [
+//         { "name": "Laptop", "brightness": 40 },
+//         { "name": "Overdriven", "brightness": 150 },
+//         { "name": "Unplugged", "error": "device disconnected" },
+//         { "name": "External", "brightness": 70, "ddc": true, "features": { "contrast": 50, "input": 15 } },
+//         { "name": "Identified", "brightness": 20, "connector": "DP-2", "serial": "ABC123" },
+//         { "name": "Dumped", "brightness": 60, "edid": "dell.bin" }
+//     ]
+// }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FakeMonitor {
+    pub name: String,
+
+    #[serde(default)]
+    pub brightness: u32,
+
+    // device fails to open during enumeration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    // brightness goes through the DDC/CI codec and an emulated display
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ddc: bool,
+
+    // values of other features, keyed by feature name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub features: BTreeMap<String, u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connector: Option<String>,
+
+    // EDID serial, overrides the serial from `edid`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+
+    // path to a binary EDID dump, relative paths start in the directory of the description file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edid: Option<PathBuf>
+}
+
+impl FakeMonitor {
+    fn value(&self, feature: Feature) -> Option<u32> {
+        match feature {
+            Feature::Brightness => Some(self.brightness),
+            _ => self.features.get(&feature.to_string()).copied()
+        }
+    }
+
+    fn set_value(&mut self, feature: Feature, value: u32) {
+        match feature {
+            Feature::Brightness => self.brightness = value,
+            _ => { self.features.insert(feature.to_string(), value); }
+        };
+    }
+
+    fn emulated_display(&self, feature: Feature) -> Ddc<LoopbackTransport> {
+        let mut transport: LoopbackTransport = LoopbackTransport::default();
+        if let Some(value) = self.value(feature) {
+            transport = transport.with_value(feature.vcp_code(), feature.is_continuous(), value as u16, max_for(feature) as u16);
+        }
+
+        Ddc::new(transport).without_delays()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FakeDescription {
+    monitors: Vec<FakeMonitor>
+}
+
+#[derive(Debug)]
+struct FakeState {
+    path: PathBuf,
+    monitors: Mutex<Vec<FakeMonitor>>
+}
+
+impl FakeState {
+    fn save(&self, monitors: &[FakeMonitor]) -> Result<(), String> {
+        let description = FakeDescription { monitors: monitors.to_vec() };
+        let serialized: String = serde_json::to_string_pretty(&description)
+            .map_err(|err| format!("Failed to serialize fake monitors ({})", err))?;
+
+        fs::write(&self.path, serialized)
+            .map_err(|err| format!("Failed to write fake monitor file `{}` ({})", self.path.display(), err))
+    }
+}
+
+/// Backend made of virtual monitors described in a JSON file (`MONB_FAKE_FILE`).
+/// Every `set` is written back to the same file, so the state survives between invocations.
+#[derive(Debug)]
+pub struct FakeBackend {
+    state: Arc<FakeState>
+}
+
+#[derive(Debug)]
+pub struct FakeDevice {
+    index: usize,
+    state: Arc<FakeState>
+}
+
+impl FakeBackend {
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var_os(FAKE_FILE_ENV) {
+            Some(path) => Self::from_file(PathBuf::from(path)),
+            None => Err(format!("Fake backend requires `{}` to point to a monitor description file", FAKE_FILE_ENV))
+        }
+    }
+
+    pub fn from_file(path: PathBuf) -> Result<Self, String> {
+        debug!("Loading fake monitors from `{}`", path.display());
+
+        let content: String = fs::read_to_string(&path)
+            .map_err(|err| format!("Failed to read fake monitor file `{}` ({})", path.display(), err))?;
+
+        let description: FakeDescription = serde_json::from_str(&content)
+            .map_err(|err| format!("Invalid fake monitor file `{}` ({})", path.display(), err))?;
+
+        return Ok(Self {
+            state: Arc::new(FakeState { path, monitors: Mutex::new(description.monitors) })
+        });
+    }
+}
+
+impl BrightnessBackend for FakeBackend {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn enumerate(&self) -> Vec<Result<Box<dyn BackendDevice>, String>> {
+        let monitors = self.state.monitors.lock().expect("Fake monitor state poisoned");
+
+        monitors.iter()
+            .enumerate()
+            .map(|(index, monitor)| match &monitor.error {
+                Some(err) => Err(format!("{} ({})", err, monitor.name)),
+                None => Ok(Box::new(FakeDevice { index, state: Arc::clone(&self.state) }) as Box<dyn BackendDevice>)
+            })
+            .collect()
+    }
+}
+
+impl FakeDevice {
+    fn with_monitor<T, F: FnOnce(&FakeMonitor) -> T>(&self, f: F) -> Option<T> {
+        self.state.monitors.lock().ok().and_then(|monitors| monitors.get(self.index).map(f))
+    }
+
+    fn missing(&self) -> String {
+        format!("Fake monitor with index `{}` doesn't exist", self.index)
+    }
+}
+
+impl BackendDevice for FakeDevice {
+    fn get(&self, feature: Feature) -> Result<u32, String> {
+        let monitors = self.state.monitors.lock().map_err(|err| err.to_string())?;
+        let monitor: &FakeMonitor = monitors.get(self.index).ok_or_else(|| self.missing())?;
+
+        if monitor.ddc {
+            return Ok(monitor.emulated_display(feature).get_vcp(feature.vcp_code())?.current as u32);
+        }
+
+        return monitor.value(feature).ok_or_else(|| unsupported_feature("fake", feature));
+    }
+
+    fn set(&self, feature: Feature, value: u32) -> Result<(), DeviceError> {
+        let mut monitors = self.state.monitors.lock().map_err(|err| err.to_string())?;
+        let monitor: &mut FakeMonitor = monitors.get_mut(self.index).ok_or_else(|| self.missing())?;
+
+        let new_value: u32 = if monitor.ddc {
+            let mut display: Ddc<LoopbackTransport> = monitor.emulated_display(feature);
+            display.set_vcp(feature.vcp_code(), value as u16)?;
+
+            match display.transport().value(feature.vcp_code()) {
+                Some(v) => v.current as u32,
+                None => return Err(unsupported_feature("fake", feature).into())
+            }
+        } else {
+            if monitor.value(feature).is_none() {
+                return Err(unsupported_feature("fake", feature).into());
+            }
+
+            value
+        };
+
+        // the value only changes once it's saved, so a failed write leaves the monitor where it was
+        let mut changed: Vec<FakeMonitor> = monitors.clone();
+        changed[self.index].set_value(feature, new_value);
+        self.state.save(&changed)?;
+
+        *monitors = changed;
+        return Ok(());
+    }
+
+    fn describe(&self, index: usize) -> String {
+        match self.with_monitor(|monitor| monitor.name.clone()) {
+            Some(name) => format!("Monitor #{} ({})", index, name),
+            None => format!("Monitor #{}", index)
+        }
+    }
+
+    fn id(&self) -> String {
+        self.with_monitor(|monitor| monitor.name.clone()).unwrap_or_default()
+    }
+
+    fn connector(&self) -> Option<String> {
+        self.with_monitor(|monitor| monitor.connector.clone()).flatten()
+    }
+
+    fn edid(&self) -> Result<Option<Edid>, String> {
+        let path: PathBuf = match self.with_monitor(|monitor| monitor.edid.clone()).flatten() {
+            Some(p) => match self.state.path.parent() {
+                Some(directory) => directory.join(p), // absolute paths replace the directory
+                None => p
+            },
+            None => return Ok(None)
+        };
+
+        let bytes: Vec<u8> = fs::read(&path).map_err(|err| format!("Failed to read `{}` ({})", path.display(), err))?;
+        Edid::parse(&bytes).map(Some).map_err(|err| format!("{} (`{}`)", err, path.display()))
+    }
+
+    fn serial(&self) -> Option<String> {
+        match self.with_monitor(|monitor| monitor.serial.clone()).flatten() {
+            Some(serial) => Some(serial),
+            None => self.edid().ok().flatten().and_then(|edid| edid.serial())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    use super::*;
+    use crate::cli::params::{BAction, BArg, BScope, BValue};
+    use crate::monitor::{apply_setter_barg, get_all_devices, Monitor};
+
+    const EDID_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/edid/dell_u2415.bin");
+
+    fn description(directory: &Path, monitors: &str) -> FakeBackend {
+        let path: PathBuf = directory.join("monitors.json");
+        fs::write(&path, format!(r#"{{ "monitors": [{}] }}"#, monitors)).unwrap();
+        return FakeBackend::from_file(path).unwrap();
+    }
+
+    fn backend(directory: &Path, edid: &str) -> FakeBackend {
+        description(directory, &format!(r#"{{ "name": "Dumped", "brightness": 60, "edid": {:?} }}"#, edid))
+    }
+
+    // monitors the way the command line sees them, with an empty configuration
+    fn monitors(backend: &FakeBackend, directory: &Path) -> Result<HashMap<usize, Monitor>, String> {
+        let config: PathBuf = directory.join("config.toml");
+        fs::write(&config, "").unwrap();
+        return get_all_devices(backend, &crate::arguments(&["monb", "-c", config.to_str().unwrap()]));
+    }
+
+    fn saved_brightness(directory: &Path, index: usize) -> u32 {
+        let description: FakeDescription = serde_json::from_str(&fs::read_to_string(directory.join("monitors.json")).unwrap()).unwrap();
+        return description.monitors[index].brightness;
+    }
+
+    fn change(monitor: &Monitor, action: BAction, brightness: usize) -> Result<(), String> {
+        apply_setter_barg(monitor, &BArg::new(BScope::Index(monitor.index), BValue::new(action, brightness)), None)
+    }
+
+    fn model_name(backend: &FakeBackend) -> Result<Option<String>, String> {
+        let devices: Vec<Result<Box<dyn BackendDevice>, String>> = backend.enumerate();
+        let device: &dyn BackendDevice = devices[0].as_ref().unwrap().as_ref();
+        return device.edid().map(|edid| edid.and_then(|edid| edid.model_name));
+    }
+
+    #[test]
+    fn relative_edid_paths_start_next_to_the_description_file() {
+        let directory = tempfile::tempdir().unwrap();
+        fs::create_dir(directory.path().join("dumps")).unwrap();
+        fs::copy(EDID_FIXTURE, directory.path().join("dumps/dell.bin")).unwrap();
+
+        assert_eq!(model_name(&backend(directory.path(), "dumps/dell.bin")), Ok(Some("DELL U2415".into())));
+    }
+
+    #[test]
+    fn absolute_edid_paths_are_used_as_they_are() {
+        let directory = tempfile::tempdir().unwrap();
+
+        assert_eq!(model_name(&backend(directory.path(), EDID_FIXTURE)), Ok(Some("DELL U2415".into())));
+    }
+
+    #[test]
+    fn missing_edid_dumps_are_reported_with_the_resolved_path() {
+        let directory = tempfile::tempdir().unwrap();
+        let expected: PathBuf = directory.path().join("dell.bin");
+
+        let err: String = model_name(&backend(directory.path(), "dell.bin")).unwrap_err();
+        assert!(err.starts_with(&format!("Failed to read `{}` (", expected.display())), "{}", err);
+    }
+
+    #[test]
+    fn increments_above_max_are_clamped() {
+        let directory = tempfile::tempdir().unwrap();
+        let backend: FakeBackend = description(directory.path(), r#"{ "name": "Laptop", "brightness": 40 }"#);
+        let devices: HashMap<usize, Monitor> = monitors(&backend, directory.path()).unwrap();
+
+        assert_eq!(change(&devices[&1], BAction::Inc, 80), Ok(()));
+        assert_eq!(saved_brightness(directory.path(), 0), 100);
+    }
+
+    #[test]
+    fn values_above_max_are_clamped_before_a_change() {
+        let directory = tempfile::tempdir().unwrap();
+        let backend: FakeBackend = description(directory.path(), r#"{ "name": "Overdriven", "brightness": 150 }"#);
+        let devices: HashMap<usize, Monitor> = monitors(&backend, directory.path()).unwrap();
+
+        assert_eq!(change(&devices[&1], BAction::Dec, 10), Ok(()));
+        assert_eq!(saved_brightness(directory.path(), 0), 90);
+    }
+
+    #[test]
+    fn ddc_writes_above_max_are_clamped_by_the_display() {
+        let directory = tempfile::tempdir().unwrap();
+        let backend: FakeBackend = description(directory.path(), r#"{ "name": "External", "brightness": 70, "ddc": true }"#);
+        let devices: Vec<Result<Box<dyn BackendDevice>, String>> = backend.enumerate();
+        let device: &dyn BackendDevice = devices[0].as_ref().unwrap().as_ref();
+
+        device.set(Feature::Brightness, 150).unwrap();
+        assert_eq!(device.get(Feature::Brightness), Ok(100));
+        assert_eq!(saved_brightness(directory.path(), 0), 100);
+    }
+
+    #[test]
+    fn emulated_displays_report_codes_as_non_continuous() {
+        let monitor: FakeMonitor = serde_json::from_str(r#"{ "name": "External", "brightness": 70, "ddc": true, "features": { "input": 15 } }"#).unwrap();
+
+        let continuous = |feature: Feature| monitor.emulated_display(feature).transport().value(feature.vcp_code()).map(|value| value.continuous);
+        assert_eq!(continuous(Feature::Brightness), Some(true));
+        assert_eq!(continuous(Feature::InputSource), Some(false));
+    }
+
+    #[test]
+    fn failing_devices_are_skipped() {
+        let directory = tempfile::tempdir().unwrap();
+        let backend: FakeBackend = description(directory.path(), r#"
+            { "name": "Laptop", "brightness": 40 },
+            { "name": "Unplugged", "error": "device disconnected" }
+        "#);
+
+        assert_eq!(backend.enumerate()[1].as_ref().unwrap_err(), "device disconnected (Unplugged)");
+
+        let devices: HashMap<usize, Monitor> = monitors(&backend, directory.path()).unwrap();
+        assert_eq!(devices.keys().copied().collect::<Vec<usize>>(), vec![1]);
+    }
+
+    #[test]
+    fn only_failing_devices_are_an_error() {
+        let directory = tempfile::tempdir().unwrap();
+        let backend: FakeBackend = description(directory.path(), r#"{ "name": "Unplugged", "error": "device disconnected" }"#);
+
+        assert_eq!(monitors(&backend, directory.path()).err(), Some("No valid monitors found :(".into()));
+    }
+
+    #[test]
+    fn unknown_device_index_is_an_error() {
+        let directory = tempfile::tempdir().unwrap();
+        let backend: FakeBackend = description(directory.path(), r#"{ "name": "Laptop", "brightness": 40 }"#);
+        let device: FakeDevice = FakeDevice { index: 3, state: Arc::clone(&backend.state) };
+
+        assert_eq!(device.get(Feature::Brightness), Err("Fake monitor with index `3` doesn't exist".into()));
+        assert_eq!(device.set(Feature::Brightness, 50).unwrap_err().to_string(), "Fake monitor with index `3` doesn't exist");
+        assert_eq!(device.describe(4), "Monitor #4");
+        assert_eq!(saved_brightness(directory.path(), 0), 40);
+    }
+
+    #[test]
+    fn unsupported_features_are_errors() {
+        let directory = tempfile::tempdir().unwrap();
+        let backend: FakeBackend = description(directory.path(), r#"{ "name": "Laptop", "brightness": 40 }"#);
+        let devices: Vec<Result<Box<dyn BackendDevice>, String>> = backend.enumerate();
+        let device: &dyn BackendDevice = devices[0].as_ref().unwrap().as_ref();
+
+        assert_eq!(device.get(Feature::Contrast), Err("Feature `contrast` is not supported by the `fake` backend".into()));
+        assert_eq!(device.set(Feature::Contrast, 50).unwrap_err().to_string(), "Feature `contrast` is not supported by the `fake` backend");
+    }
+
+    #[test]
+    fn malformed_descriptions_are_reported_with_their_path() {
+        let directory = tempfile::tempdir().unwrap();
+        let path: PathBuf = directory.path().join("monitors.json");
+        fs::write(&path, r#"{ "monitors": [{ "name": "Laptop", "brightness": }] }"#).unwrap();
+
+        let err: String = FakeBackend::from_file(path.clone()).unwrap_err();
+        assert!(err.starts_with(&format!("Invalid fake monitor file `{}` (", path.display())), "{}", err);
+    }
+
+    #[test]
+    fn missing_descriptions_are_reported_with_their_path() {
+        let directory = tempfile::tempdir().unwrap();
+        let path: PathBuf = directory.path().join("missing.json");
+
+        let err: String = FakeBackend::from_file(path.clone()).unwrap_err();
+        assert!(err.starts_with(&format!("Failed to read fake monitor file `{}` (", path.display())), "{}", err);
+    }
+}
@@ -0,0 +1,209 @@
+use log::debug;
+use once_cell::sync::OnceCell;
+use zbus::blocking::{Connection, ConnectionBuilder};
+
+pub const LOGIND_BUS_ADDRESS_ENV: &'static str = "MONB_LOGIND_BUS_ADDRESS";
+
+const LOGIND_DBUS_NAME: &'static str = "org.freedesktop.login1";
+const SESSION_OBJECT_PATH: &'static str = "/org/freedesktop/login1/session/auto";
+const SESSION_INTERFACE: &'static str = "org.freedesktop.login1.Session";
+const SET_BRIGHTNESS_METHOD: &'static str = "SetBrightness";
+const INTROSPECTABLE_INTERFACE: &'static str = "org.freedesktop.DBus.Introspectable";
+const INTROSPECT_METHOD: &'static str = "Introspect";
+
+static SESSION: OnceCell<Option<LogindSession>> = OnceCell::new();
+
+/// Device that systemd-logind can set the brightness of, `value` is in raw device units.
+#[derive(Debug)]
+pub struct LogindTarget {
+    pub subsystem: &'static str,
+    pub name: String,
+    pub value: u32
+}
+
+/// Current login session, used to set the brightness without write access to sysfs.
+#[derive(Debug)]
+pub struct LogindSession {
+    connection: Connection
+}
+
+impl LogindSession {
+    // the system bus is used unless `MONB_LOGIND_BUS_ADDRESS` is set, e.g. to a private bus with a mock login1 service
+    fn connect() -> Result<Self, String> {
+        match std::env::var(LOGIND_BUS_ADDRESS_ENV) {
+            Ok(address) => Self::connect_to(&address),
+            Err(_) => Connection::system()
+                .map(|connection| Self { connection })
+                .map_err(|err| format!("Failed to connect to D-Bus ({})", err))
+        }
+    }
+
+    fn connect_to(address: &str) -> Result<Self, String> {
+        debug!("Connecting to systemd-logind on `{}`", address);
+        let connection: Connection = ConnectionBuilder::address(address)
+            .and_then(|builder| builder.build())
+            .map_err(|err| format!("Failed to connect to D-Bus ({})", err))?;
+
+        return Ok(Self { connection });
+    }
+
+    // `SetBrightness` was added in systemd 243, older sessions don't have it
+    fn supports_set_brightness(&self) -> Result<bool, String> {
+        let reply = self.connection.call_method(
+            Some(LOGIND_DBUS_NAME),
+            SESSION_OBJECT_PATH,
+            Some(INTROSPECTABLE_INTERFACE),
+            INTROSPECT_METHOD,
+            &()
+        ).map_err(|err| format!("Failed to introspect the login session ({})", err))?;
+
+        let introspection: String = reply.body::<String>()
+            .map_err(|err| format!("Invalid introspection data ({})", err))?;
+
+        return Ok(introspection.contains(SESSION_INTERFACE) && introspection.contains(SET_BRIGHTNESS_METHOD));
+    }
+
+    pub fn set_brightness(&self, target: &LogindTarget) -> Result<(), String> {
+        debug!("Setting `{}/{}` to `{}` through systemd-logind", target.subsystem, target.name, target.value);
+
+        self.connection.call_method(
+            Some(LOGIND_DBUS_NAME),
+            SESSION_OBJECT_PATH,
+            Some(SESSION_INTERFACE),
+            SET_BRIGHTNESS_METHOD,
+            &(target.subsystem, target.name.as_str(), target.value)
+        ).map_err(|err| format!("systemd-logind failed to set the brightness ({})", err))?;
+
+        return Ok(());
+    }
+}
+
+/// Returns the login session if it supports `SetBrightness`. The check is done only once per process.
+pub fn session() -> Option<&'static LogindSession> {
+    SESSION.get_or_init(|| {
+        let session: LogindSession = match LogindSession::connect() {
+            Ok(s) => s,
+            Err(err) => { debug!("{}", err); return None; }
+        };
+
+        match session.supports_set_brightness() {
+            Ok(true) => Some(session),
+            Ok(false) => { debug!("Login session doesn't support `{}`", SET_BRIGHTNESS_METHOD); None },
+            Err(err) => { debug!("{}", err); None }
+        }
+    }).as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, ErrorKind};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+
+    use zbus::dbus_interface;
+
+    use super::*;
+
+    type Calls = Arc<Mutex<Vec<(String, String, u32)>>>;
+
+    // private session bus, killed when the test ends
+    struct Bus {
+        daemon: Child,
+        address: String
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    // `None` if `dbus-daemon` isn't installed
+    fn private_bus() -> Option<Bus> {
+        let mut daemon: Child = match Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(d) => d,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                eprintln!("`dbus-daemon` not found, skipping the mock login1 service test");
+                return None;
+            },
+            Err(err) => panic!("Failed to start `dbus-daemon` ({})", err)
+        };
+
+        let mut address: String = String::new();
+        BufReader::new(daemon.stdout.take().expect("Missing `dbus-daemon` stdout")).read_line(&mut address).expect("Failed to read the bus address");
+
+        return Some(Bus { daemon, address: address.trim().to_string() });
+    }
+
+    struct MockSession {
+        calls: Calls
+    }
+
+    #[dbus_interface(name = "org.freedesktop.login1.Session")]
+    impl MockSession {
+        fn set_brightness(&self, subsystem: &str, name: &str, value: u32) {
+            self.calls.lock().unwrap().push((subsystem.to_string(), name.to_string(), value));
+        }
+    }
+
+    // session of systemd older than 243
+    struct OldMockSession;
+
+    #[dbus_interface(name = "org.freedesktop.login1.Session")]
+    impl OldMockSession {
+        fn lock(&self) {}
+    }
+
+    fn serve<I: zbus::Interface>(bus: &Bus, session: I) -> Connection {
+        ConnectionBuilder::address(bus.address.as_str())
+            .and_then(|builder| builder.name(LOGIND_DBUS_NAME))
+            .and_then(|builder| builder.serve_at(SESSION_OBJECT_PATH, session))
+            .and_then(|builder| builder.build())
+            .expect("Failed to serve the mock login1 session")
+    }
+
+    #[test]
+    fn set_brightness_is_called_with_subsystem_name_and_value() {
+        let bus: Bus = match private_bus() {
+            Some(b) => b,
+            None => return
+        };
+        let calls: Calls = Calls::default();
+        let _service: Connection = serve(&bus, MockSession { calls: Arc::clone(&calls) });
+
+        let session: LogindSession = LogindSession::connect_to(&bus.address).unwrap();
+        assert_eq!(session.supports_set_brightness(), Ok(true));
+
+        session.set_brightness(&LogindTarget { subsystem: "backlight", name: "intel_backlight".into(), value: 469 }).unwrap();
+        assert_eq!(*calls.lock().unwrap(), [("backlight".to_string(), "intel_backlight".to_string(), 469)]);
+    }
+
+    #[test]
+    fn sessions_without_set_brightness_are_not_supported() {
+        let bus: Bus = match private_bus() {
+            Some(b) => b,
+            None => return
+        };
+        let _service: Connection = serve(&bus, OldMockSession);
+
+        let session: LogindSession = LogindSession::connect_to(&bus.address).unwrap();
+        assert_eq!(session.supports_set_brightness(), Ok(false));
+    }
+
+    #[test]
+    fn missing_login1_service_is_an_error() {
+        let bus: Bus = match private_bus() {
+            Some(b) => b,
+            None => return
+        };
+
+        let session: LogindSession = LogindSession::connect_to(&bus.address).unwrap();
+        assert!(session.supports_set_brightness().is_err_and(|err| err.starts_with("Failed to introspect the login session")));
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt::{Debug, Display};
+
+use log::{debug, warn};
+use strum::{Display, EnumIter, IntoStaticStr};
+
+use crate::edid::Edid;
+use crate::feature::Feature;
+
+mod fake;
+mod native;
+
+#[cfg(target_os = "linux")]
+mod sysfs;
+
+#[cfg(target_os = "linux")]
+mod ddc;
+
+#[cfg(target_os = "linux")]
+mod drm;
+
+#[cfg(target_os = "linux")]
+pub mod logind;
+
+pub use fake::FakeBackend;
+pub use native::NativeBackend;
+
+#[cfg(target_os = "linux")]
+pub use sysfs::SysfsBackend;
+
+#[cfg(target_os = "linux")]
+pub use ddc::DdcBackend;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(EnumIter, IntoStaticStr, Display)]
+pub enum BackendType {
+    #[default]
+    #[strum(serialize = "native")]  Native,
+
+    #[strum(serialize = "fake")]    Fake,
+
+    #[cfg(target_os = "linux")]
+    #[strum(serialize = "sysfs")]   Sysfs,
+
+    #[cfg(target_os = "linux")]
+    #[strum(serialize = "ddc")]     Ddc
+}
+
+impl TryFrom<Option<&String>> for BackendType {
+    type Error = String;
+
+    fn try_from(value: Option<&String>) -> Result<Self, Self::Error> {
+        let potential_backend: &String = match value {
+            None => return Ok(Self::default()),
+            Some(v) => v
+        };
+
+        debug!("Parsing backend: `{}`", potential_backend);
+
+        match potential_backend.trim().to_lowercase().as_str() {
+            "native" => Ok(Self::Native),
+            "fake" => Ok(Self::Fake),
+
+            #[cfg(target_os = "linux")]
+            "sysfs" => Ok(Self::Sysfs),
+
+            #[cfg(target_os = "linux")]
+            "ddc" => Ok(Self::Ddc),
+
+            _ => Err(format!("Invalid backend: `{}`", potential_backend))
+        }
+    }
+}
+
+impl BackendType {
+    pub fn create(&self, raw: bool) -> Result<Box<dyn BrightnessBackend>, String> {
+        if raw && !self.supports_raw() {
+            warn!("Backend `{}` doesn't support raw device values, using percentages", self);
+        }
+
+        match self {
+            Self::Native => Ok(Box::new(NativeBackend)),
+            Self::Fake => Ok(Box::new(FakeBackend::from_env()?)),
+
+            #[cfg(target_os = "linux")]
+            Self::Sysfs => Ok(Box::new(SysfsBackend::from_env(raw))),
+
+            #[cfg(target_os = "linux")]
+            Self::Ddc => Ok(Box::new(DdcBackend::new(raw)))
+        }
+    }
+
+    pub fn supports_raw(&self) -> bool {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::Sysfs | Self::Ddc => true,
+
+            _ => false
+        }
+    }
+}
+
+/// Built-in panel or a separate display, selected with the `internal` and `external` scopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(EnumIter, IntoStaticStr, Display)]
+pub enum MonitorKind {
+    #[strum(serialize = "internal")]    Internal,
+    #[strum(serialize = "external")]    External,
+
+    // backend can't tell, matches neither scope
+    #[strum(serialize = "unknown")]     Unknown
+}
+
+impl MonitorKind {
+    /// `eDP-1`, `LVDS-1` and `DSI-1` are built-in panels, every other connector type (`DP`, `HDMI-A`, `DVI-D`, ...) is external.
+    pub fn from_connector(connector: &str) -> Self {
+        let connector_type: &str = connector.split('-').next().unwrap_or_default();
+        match connector_type.to_uppercase().as_str() {
+            "EDP" | "LVDS" | "DSI" => Self::Internal,
+            _ => Self::External
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DeviceError {
+    PermissionDenied(String),
+    Other(String)
+}
+
+impl Display for DeviceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PermissionDenied(message) => write!(f, "{}", message),
+            Self::Other(message) => write!(f, "{}", message)
+        }
+    }
+}
+
+/// Largest value of a feature for devices that don't report their own range.
+pub fn max_for(feature: Feature) -> u32 {
+    crate::ternary_operator!(feature.is_continuous(), 100, u16::MAX as u32)
+}
+
+pub fn unsupported_feature(backend: &str, feature: Feature) -> String {
+    format!("Feature `{}` is not supported by the `{}` backend", feature, backend)
+}
+
+impl From<String> for DeviceError {
+    fn from(value: String) -> Self {
+        Self::Other(value)
+    }
+}
+
+/// Source of brightness devices (the `brightness` crate, sysfs, DDC/CI, ...).
+pub trait BrightnessBackend {
+    /// Short backend name used in log messages.
+    fn name(&self) -> &'static str;
+
+    /// Lists every device the backend can see. Devices that could not be opened are returned as errors
+    /// so the caller can report them without losing the enumeration order.
+    fn enumerate(&self) -> Vec<Result<Box<dyn BackendDevice>, String>>;
+}
+
+/// Single device returned by [`BrightnessBackend::enumerate`].
+/// Values are in the `0..=max(feature)` range, which is a percentage for continuous features,
+/// unless the device was opened in raw mode.
+pub trait BackendDevice: Debug + Send + Sync {
+    fn get(&self, feature: Feature) -> Result<u32, String>;
+    fn set(&self, feature: Feature, value: u32) -> Result<(), DeviceError>;
+
+    /// Human readable name of the device, `index` is the (already offset) enumeration index.
+    fn describe(&self, index: usize) -> String;
+
+    /// Stable identity used to key per-monitor configuration, doesn't depend on the enumeration order.
+    fn id(&self) -> String;
+
+    /// Display connector (`DP-1`, `eDP-1`, ...), if the backend knows it.
+    fn connector(&self) -> Option<String> {
+        None
+    }
+
+    fn kind(&self) -> MonitorKind {
+        match self.connector() {
+            Some(connector) => MonitorKind::from_connector(&connector),
+            None => MonitorKind::Unknown
+        }
+    }
+
+    /// EDID of the display, `None` if the backend can't read it.
+    fn edid(&self) -> Result<Option<Edid>, String> {
+        Ok(None)
+    }
+
+    /// Serial reported by the display EDID.
+    fn serial(&self) -> Option<String> {
+        match self.edid() {
+            Ok(edid) => edid.and_then(|edid| edid.serial()),
+            Err(err) => {
+                debug!("{}", err);
+                None
+            }
+        }
+    }
+
+    fn max(&self, feature: Feature) -> u32 {
+        max_for(feature)
+    }
+
+    /// Target for systemd-logind's `SetBrightness`, used when `set` fails with [`DeviceError::PermissionDenied`].
+    #[cfg(target_os = "linux")]
+    fn logind_target(&self, _value: u32) -> Result<Option<logind::LogindTarget>, String> {
+        Ok(None)
+    }
+}
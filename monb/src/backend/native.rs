@@ -0,0 +1,131 @@
+use brightness::blocking::{brightness_devices, Brightness, BrightnessDevice};
+
+use crate::backend::{unsupported_feature, BackendDevice, BrightnessBackend, DeviceError};
+use crate::feature::Feature;
+
+#[cfg(target_os = "linux")]
+use crate::backend::{drm, MonitorKind};
+
+#[cfg(target_os = "linux")]
+use crate::edid::Edid;
+
+#[cfg(windows)]
+use self::windows::get_device_name;
+
+#[cfg(target_os = "linux")]
+use self::linux::get_device_name;
+
+/// Backend built on top of the `brightness` crate.
+#[derive(Debug, Default)]
+pub struct NativeBackend;
+
+#[derive(Debug)]
+pub struct NativeDevice {
+    device: BrightnessDevice
+}
+
+impl BrightnessBackend for NativeBackend {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn enumerate(&self) -> Vec<Result<Box<dyn BackendDevice>, String>> {
+        brightness_devices()
+            .map(|potential_device| match potential_device {
+                Ok(device) => Ok(Box::new(NativeDevice { device }) as Box<dyn BackendDevice>),
+                Err(err) => Err(err.to_string())
+            })
+            .collect()
+    }
+}
+
+impl BackendDevice for NativeDevice {
+    fn get(&self, feature: Feature) -> Result<u32, String> {
+        if feature != Feature::Brightness {
+            return Err(unsupported_feature("native", feature));
+        }
+
+        self.device.get().map_err(|err| err.to_string())
+    }
+
+    fn set(&self, feature: Feature, value: u32) -> Result<(), DeviceError> {
+        if feature != Feature::Brightness {
+            return Err(unsupported_feature("native", feature).into());
+        }
+
+        self.device.set(value).map_err(|err| DeviceError::Other(err.to_string()))
+    }
+
+    fn describe(&self, index: usize) -> String {
+        get_device_name(&self.device, index)
+    }
+
+    fn id(&self) -> String {
+        // backlight name on linux, display device path on windows
+        self.device.device_name().unwrap_or_default()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn connector(&self) -> Option<String> {
+        linux::backlight_connector(&self.id()).map(|(connector, _)| connector)
+    }
+
+    // the `brightness` crate only sees backlights on linux, so they are built-in panels
+    #[cfg(target_os = "linux")]
+    fn kind(&self) -> MonitorKind {
+        self.connector().map_or(MonitorKind::Internal, |connector| MonitorKind::from_connector(&connector))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn edid(&self) -> Result<Option<Edid>, String> {
+        match linux::backlight_connector(&self.id()) {
+            Some((_, connector_path)) => drm::edid(&connector_path),
+            None => Ok(None)
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use brightness::blocking::BrightnessDevice;
+    use brightness::blocking::windows::BrightnessExt;
+    use log::debug;
+
+    pub fn get_device_name(device: &BrightnessDevice, index: usize) -> String {
+        match device.device_description() {
+            Ok(device_description) => format!("Monitor #{} ({})", index, device_description),
+            Err(err) => {
+                debug!("Monitor #{} - {}", index, err.to_string());
+                format!("Monitor #{}", index)
+            }
+        }
+    }
+}
+
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::path::{Path, PathBuf};
+
+    use brightness::blocking::{Brightness, BrightnessDevice};
+    use log::debug;
+
+    use crate::backend::drm;
+
+    // the `brightness` crate always uses the real sysfs
+    const BACKLIGHT_ROOT: &'static str = "/sys/class/backlight";
+
+    pub fn get_device_name(device: &BrightnessDevice, index: usize) -> String {
+        match device.device_name() {
+            Ok(device_name) => format!("Monitor #{} ({})", index, device_name),
+            Err(err) => {
+                debug!("Monitor #{} - {}", index, err.to_string());
+                format!("Monitor #{}", index)
+            }
+        }
+    }
+
+    pub fn backlight_connector(name: &str) -> Option<(String, PathBuf)> {
+        drm::backlight_connector(&Path::new(BACKLIGHT_ROOT).join(name))
+    }
+}
@@ -0,0 +1,335 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+use crate::backend::{drm, unsupported_feature, BackendDevice, BrightnessBackend, DeviceError, MonitorKind};
+use crate::edid::Edid;
+use crate::feature::Feature;
+use crate::backend::logind::LogindTarget;
+
+pub const SYSFS_ROOT_ENV: &'static str = "MONB_SYSFS_ROOT";
+pub const SYSFS_ROOT_DEFAULT: &'static str = "/sys/class/backlight";
+
+const BRIGHTNESS_FILE: &'static str = "brightness";
+const MAX_BRIGHTNESS_FILE: &'static str = "max_brightness";
+
+/// Backend reading and writing `<root>/*/brightness` directly.
+/// The root defaults to `/sys/class/backlight` and can be moved with `MONB_SYSFS_ROOT`, e.g. to a fake directory tree.
+#[derive(Debug)]
+pub struct SysfsBackend {
+    root: PathBuf,
+    raw: bool
+}
+
+/// Single backlight device, `raw` devices use the `0..=max_brightness` range instead of percentages.
+#[derive(Debug)]
+pub struct SysfsDevice {
+    name: String,
+    path: PathBuf,
+    raw: bool
+}
+
+impl SysfsBackend {
+    pub fn new(root: PathBuf, raw: bool) -> Self {
+        Self { root, raw }
+    }
+
+    pub fn from_env(raw: bool) -> Self {
+        let root: PathBuf = match std::env::var_os(SYSFS_ROOT_ENV) {
+            Some(r) => PathBuf::from(r),
+            None => PathBuf::from(SYSFS_ROOT_DEFAULT)
+        };
+
+        Self::new(root, raw)
+    }
+}
+
+impl BrightnessBackend for SysfsBackend {
+    fn name(&self) -> &'static str {
+        "sysfs"
+    }
+
+    fn enumerate(&self) -> Vec<Result<Box<dyn BackendDevice>, String>> {
+        let entries = match fs::read_dir(&self.root) {
+            Ok(e) => e,
+            Err(err) => {
+                debug!("Failed to read sysfs root `{}` ({})", self.root.display(), err);
+                return vec![];
+            }
+        };
+
+        // sort by name, so the enumeration order doesn't depend on the filesystem
+        let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+        paths.sort();
+
+        paths.into_iter()
+            .map(|path| {
+                let device = SysfsDevice {
+                    name: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                    path,
+                    raw: self.raw
+                };
+
+                // make sure the device is usable before handing it out
+                device.read_value(MAX_BRIGHTNESS_FILE)?;
+                Ok(Box::new(device) as Box<dyn BackendDevice>)
+            })
+            .collect()
+    }
+}
+
+impl SysfsDevice {
+    fn to_raw_value(&self, value: u32) -> Result<u32, String> {
+        let max: u32 = self.read_value(MAX_BRIGHTNESS_FILE)?;
+        return Ok(if self.raw { value.min(max) } else { from_percentage(value, max) });
+    }
+
+    fn read_value(&self, file: &'static str) -> Result<u32, String> {
+        let path: PathBuf = self.path.join(file);
+        let content: String = fs::read_to_string(&path)
+            .map_err(|err| format!("Failed to read `{}` ({})", path.display(), err))?;
+
+        content.trim().parse::<u32>()
+            .map_err(|_| format!("`{}` contains invalid value `{}`", path.display(), content.trim()))
+    }
+}
+
+impl BackendDevice for SysfsDevice {
+    fn get(&self, feature: Feature) -> Result<u32, String> {
+        if feature != Feature::Brightness {
+            return Err(unsupported_feature("sysfs", feature));
+        }
+
+        let actual: u32 = self.read_value(BRIGHTNESS_FILE)?;
+        if self.raw {
+            return Ok(actual);
+        }
+
+        let max: u32 = self.read_value(MAX_BRIGHTNESS_FILE)?;
+        return Ok(to_percentage(actual, max));
+    }
+
+    fn set(&self, feature: Feature, value: u32) -> Result<(), DeviceError> {
+        if feature != Feature::Brightness {
+            return Err(unsupported_feature("sysfs", feature).into());
+        }
+
+        let raw_value: u32 = self.to_raw_value(value)?;
+
+        let path: PathBuf = self.path.join(BRIGHTNESS_FILE);
+        debug!("Writing `{}` to `{}`", raw_value, path.display());
+
+        fs::write(&path, raw_value.to_string()).map_err(|err| write_error(&path, err))
+    }
+
+    fn describe(&self, index: usize) -> String {
+        format!("Monitor #{} ({})", index, self.name)
+    }
+
+    fn id(&self) -> String {
+        self.name.clone()
+    }
+
+    fn connector(&self) -> Option<String> {
+        drm::backlight_connector(&self.path).map(|(connector, _)| connector)
+    }
+
+    // backlights without a connector link (`acpi_video0`, ...) are still built-in panels
+    fn kind(&self) -> MonitorKind {
+        self.connector().map_or(MonitorKind::Internal, |connector| MonitorKind::from_connector(&connector))
+    }
+
+    fn edid(&self) -> Result<Option<Edid>, String> {
+        match drm::backlight_connector(&self.path) {
+            Some((_, connector_path)) => drm::edid(&connector_path),
+            None => Ok(None)
+        }
+    }
+
+    fn max(&self, _: Feature) -> u32 {
+        if self.raw {
+            // unreadable range, the following `set` will report the error
+            return self.read_value(MAX_BRIGHTNESS_FILE).unwrap_or(100);
+        }
+
+        100
+    }
+
+    fn logind_target(&self, value: u32) -> Result<Option<LogindTarget>, String> {
+        return Ok(Some(LogindTarget {
+            subsystem: "backlight",
+            name: self.name.clone(),
+            value: self.to_raw_value(value)?
+        }));
+    }
+}
+
+// permission errors let the caller fall back to logind
+fn write_error(path: &Path, err: std::io::Error) -> DeviceError {
+    match err.kind() {
+        ErrorKind::PermissionDenied => DeviceError::PermissionDenied(format!(
+            "Permission denied while writing `{}`, run as root or add a udev rule that grants write access to the backlight device",
+            path.display()
+        )),
+        _ => DeviceError::Other(format!("Failed to write `{}` ({})", path.display(), err))
+    }
+}
+
+// rounding in both directions, so `set(x)` followed by `get()` returns `x` for devices with `max >= 100`
+fn to_percentage(raw_value: u32, max: u32) -> u32 {
+    if max == 0 {
+        return 0;
+    }
+
+    ((raw_value as u64 * 100 + max as u64 / 2) / max as u64) as u32
+}
+
+fn from_percentage(percentage: u32, max: u32) -> u32 {
+    ((percentage.min(100) as u64 * max as u64 + 50) / 100) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    // `<root>/<name>/brightness` and `max_brightness` for every `(name, brightness, max_brightness)`
+    fn backlight_tree(devices: &[(&str, u32, u32)]) -> TempDir {
+        let root: TempDir = tempfile::tempdir().expect("Failed to create a temporary directory");
+        for (name, brightness, max) in devices {
+            let directory: PathBuf = root.path().join(name);
+            fs::create_dir(&directory).unwrap();
+            fs::write(directory.join(BRIGHTNESS_FILE), format!("{}\n", brightness)).unwrap();
+            fs::write(directory.join(MAX_BRIGHTNESS_FILE), format!("{}\n", max)).unwrap();
+        }
+
+        return root;
+    }
+
+    fn devices(root: &TempDir, raw: bool) -> Vec<Box<dyn BackendDevice>> {
+        SysfsBackend::new(root.path().to_path_buf(), raw).enumerate().into_iter().map(Result::unwrap).collect()
+    }
+
+    fn brightness_file(root: &TempDir, name: &str) -> String {
+        fs::read_to_string(root.path().join(name).join(BRIGHTNESS_FILE)).unwrap()
+    }
+
+    #[test]
+    fn percentages_round_to_nearest_at_odd_maxima() {
+        assert_eq!(to_percentage(1, 7), 14);
+        assert_eq!(to_percentage(4, 7), 57);
+        assert_eq!(to_percentage(3, 255), 1);
+        assert_eq!(to_percentage(128, 255), 50);
+        assert_eq!(to_percentage(5, 0), 0);
+
+        assert_eq!(from_percentage(50, 7), 4);
+        assert_eq!(from_percentage(1, 255), 3);
+        assert_eq!(from_percentage(50, 937), 469);
+        assert_eq!(from_percentage(150, 937), 937);
+    }
+
+    #[test]
+    fn percentages_round_trip_when_max_is_at_least_100() {
+        for max in [100, 101, 255, 937, 19393] {
+            for percentage in 0..=100 {
+                assert_eq!(to_percentage(from_percentage(percentage, max), max), percentage, "max `{}`", max);
+            }
+        }
+    }
+
+    #[test]
+    fn devices_are_enumerated_by_name() {
+        let root: TempDir = backlight_tree(&[("intel_backlight", 10, 100), ("acpi_video0", 3, 7)]);
+        let names: Vec<String> = devices(&root, false).iter().map(|device| device.id()).collect();
+
+        assert_eq!(names, ["acpi_video0", "intel_backlight"]);
+    }
+
+    #[test]
+    fn devices_without_max_brightness_fail_to_open() {
+        let root: TempDir = backlight_tree(&[("broken", 10, 100)]);
+        let path: PathBuf = root.path().join("broken").join(MAX_BRIGHTNESS_FILE);
+        fs::remove_file(&path).unwrap();
+
+        let enumerated: Vec<Result<Box<dyn BackendDevice>, String>> = SysfsBackend::new(root.path().to_path_buf(), false).enumerate();
+        assert_eq!(enumerated.len(), 1);
+        assert!(enumerated[0].as_ref().is_err_and(|err| err.starts_with(&format!("Failed to read `{}`", path.display()))));
+    }
+
+    #[test]
+    fn percentages_are_scaled_to_max_brightness() {
+        let root: TempDir = backlight_tree(&[("panel", 300, 937)]);
+        let opened: Vec<Box<dyn BackendDevice>> = devices(&root, false);
+        let device: &dyn BackendDevice = opened[0].as_ref();
+
+        assert_eq!(device.get(Feature::Brightness).unwrap(), 32);
+        assert_eq!(device.max(Feature::Brightness), 100);
+
+        device.set(Feature::Brightness, 50).unwrap();
+        assert_eq!(brightness_file(&root, "panel"), "469");
+        assert_eq!(device.get(Feature::Brightness).unwrap(), 50);
+    }
+
+    #[test]
+    fn raw_values_are_used_as_they_are() {
+        let root: TempDir = backlight_tree(&[("panel", 300, 937)]);
+        let opened: Vec<Box<dyn BackendDevice>> = devices(&root, true);
+        let device: &dyn BackendDevice = opened[0].as_ref();
+
+        assert_eq!(device.get(Feature::Brightness).unwrap(), 300);
+        assert_eq!(device.max(Feature::Brightness), 937);
+
+        device.set(Feature::Brightness, 50).unwrap();
+        assert_eq!(brightness_file(&root, "panel"), "50");
+
+        // clamped to `max_brightness`
+        device.set(Feature::Brightness, 5000).unwrap();
+        assert_eq!(brightness_file(&root, "panel"), "937");
+    }
+
+    #[test]
+    fn other_features_are_not_supported() {
+        let root: TempDir = backlight_tree(&[("panel", 300, 937)]);
+        let opened: Vec<Box<dyn BackendDevice>> = devices(&root, false);
+        let device: &dyn BackendDevice = opened[0].as_ref();
+
+        assert_eq!(device.get(Feature::Contrast).unwrap_err(), "Feature `contrast` is not supported by the `sysfs` backend");
+    }
+
+    #[test]
+    fn permission_errors_are_reported_as_permission_denied() {
+        let path: PathBuf = PathBuf::from("/sys/class/backlight/panel/brightness");
+        match write_error(&path, std::io::Error::from(ErrorKind::PermissionDenied)) {
+            DeviceError::PermissionDenied(message) => assert_eq!(message, concat!(
+                "Permission denied while writing `/sys/class/backlight/panel/brightness`, ",
+                "run as root or add a udev rule that grants write access to the backlight device"
+            )),
+            other => panic!("Expected a permission error, got `{}`", other)
+        };
+    }
+
+    #[test]
+    fn read_only_brightness_file_is_permission_denied() {
+        // root ignores file permissions, `permission_errors_are_reported_as_permission_denied` covers the message
+        if unsafe { libc::geteuid() } == 0 {
+            return;
+        }
+
+        let root: TempDir = backlight_tree(&[("panel", 300, 937)]);
+        let path: PathBuf = root.path().join("panel").join(BRIGHTNESS_FILE);
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o444)).unwrap();
+
+        let opened: Vec<Box<dyn BackendDevice>> = devices(&root, false);
+        let device: &dyn BackendDevice = opened[0].as_ref();
+        match device.set(Feature::Brightness, 50) {
+            Err(DeviceError::PermissionDenied(message)) => assert!(message.starts_with(&format!("Permission denied while writing `{}`", path.display()))),
+            other => panic!("Expected a permission error, got `{:?}`", other)
+        };
+        assert_eq!(brightness_file(&root, "panel"), "300\n");
+    }
+}
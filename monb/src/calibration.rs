@@ -0,0 +1,189 @@
+use serde::Deserialize;
+
+// Example profile, logical values map to device percentages:
+// [calibration."DP-1"]
+// points = [[0, 5], [50, 30], [100, 90]]
+// min = 10
+// max = 80
+/// Piecewise-linear mapping from logical brightness (what the user types) to the device percentage,
+/// scaled onto the optional `min..=max` device range. Missing `0` and `100` points map to themselves.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Calibration {
+    #[serde(default)]
+    points: Vec<[u32; 2]>,
+
+    min: Option<u32>,
+    max: Option<u32>
+}
+
+impl Calibration {
+    pub fn validate(&self) -> Result<(), String> {
+        for [logical, device] in &self.points {
+            if *logical > 100 || *device > 100 {
+                return Err(format!("Calibration point `[{}, {}]` is out of the 0..=100 range", logical, device));
+            }
+        }
+
+        for pair in self.points.windows(2) {
+            let ([previous_logical, previous_device], [logical, device]) = (pair[0], pair[1]);
+            if logical <= previous_logical {
+                return Err(format!("Calibration points must be sorted by logical brightness (`{}` after `{}`)", logical, previous_logical));
+            }
+
+            // a decreasing curve can't be inverted for getters
+            if device < previous_device {
+                return Err(format!("Calibration device values must not decrease (`{}` after `{}`)", device, previous_device));
+            }
+        }
+
+        let min: u32 = self.min.unwrap_or(0);
+        let max: u32 = self.max.unwrap_or(100);
+        if max > 100 {
+            return Err(format!("Calibration max `{}` is bigger than 100", max));
+        }
+        if min > max {
+            return Err(format!("Calibration min `{}` is bigger than max `{}`", min, max));
+        }
+
+        return Ok(());
+    }
+
+    fn curve(&self) -> Vec<(u32, u32)> {
+        let mut curve: Vec<(u32, u32)> = self.points.iter().map(|[logical, device]| (*logical, *device)).collect();
+
+        if !matches!(curve.first(), Some((0, _))) {
+            curve.insert(0, (0, 0));
+        }
+        if !matches!(curve.last(), Some((logical, _)) if *logical >= 100) {
+            curve.push((100, 100));
+        }
+
+        return curve;
+    }
+
+    // device range the whole curve is scaled onto
+    fn range(&self) -> (u32, u32) {
+        (self.min.unwrap_or(0), self.max.unwrap_or(100))
+    }
+
+    /// Logical brightness -> device percentage. Caps scale the curve instead of cutting it off,
+    /// so every device value in range reads back as the logical value that set it.
+    pub fn to_device(&self, logical: u32) -> u32 {
+        let logical: u32 = logical.min(100);
+        let curve: Vec<(u32, u32)> = self.curve();
+
+        let device: f64 = curve.windows(2)
+            .find(|pair| logical <= pair[1].0)
+            .map(|pair| interpolate(logical as f64, pair[0], pair[1]))
+            .unwrap_or(logical as f64);
+
+        // rounded once, at the end, so rounding can't move a value to a neighbouring one
+        let (min, max): (u32, u32) = self.range();
+        return interpolate(device, (0, min), (100, max)).round() as u32;
+    }
+
+    /// Device percentage -> logical brightness, the one whose device percentage is nearest.
+    /// Flat parts of the curve resolve to their lowest logical value.
+    pub fn to_logical(&self, device: u32) -> u32 {
+        // logical values are whole percentages, searching them is exact where inverting the rounded curve is not
+        (0..=100).min_by_key(|logical| self.to_device(*logical).abs_diff(device)).unwrap_or(0)
+    }
+}
+
+// linear interpolation of `x` between points `(x0, y0)` and `(x1, y1)`
+fn interpolate(x: f64, (x0, y0): (u32, u32), (x1, y1): (u32, u32)) -> f64 {
+    if x1 == x0 {
+        return y0 as f64;
+    }
+
+    let t: f64 = (x - x0 as f64) / (x1 as f64 - x0 as f64);
+    return y0 as f64 + (y1 as f64 - y0 as f64) * t;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calibration(points: &[[u32; 2]], min: Option<u32>, max: Option<u32>) -> Calibration {
+        let calibration: Calibration = Calibration { points: points.to_vec(), min, max };
+        calibration.validate().unwrap();
+        return calibration;
+    }
+
+    fn profiles() -> Vec<Calibration> {
+        vec![
+            calibration(&[], None, None),
+            calibration(&[], Some(5), None),
+            calibration(&[], Some(10), Some(80)),
+            calibration(&[[0, 5], [50, 30], [100, 90]], None, Some(80)),
+            calibration(&[[20, 0], [40, 60]], None, None),
+            calibration(&[[0, 10], [30, 10], [100, 100]], Some(5), None),
+            calibration(&[], Some(40), Some(40))
+        ]
+    }
+
+    #[test]
+    fn capped_ends_read_back_as_the_ends() {
+        for profile in profiles().into_iter().filter(|profile| profile.min != profile.max) {
+            assert_eq!(profile.to_logical(profile.to_device(0)), 0, "{:?}", profile);
+            assert_eq!(profile.to_logical(profile.to_device(100)), 100, "{:?}", profile);
+        }
+
+        // `monb 1:0` followed by `monb 1` with `min = 5`
+        let profile: Calibration = calibration(&[], Some(5), None);
+        assert_eq!(profile.to_device(0), 5);
+        assert_eq!(profile.to_logical(5), 0);
+    }
+
+    #[test]
+    fn device_values_round_trip() {
+        for profile in profiles() {
+            for logical in 0..=100 {
+                let device: u32 = profile.to_device(logical);
+                assert_eq!(profile.to_device(profile.to_logical(device)), device, "{:?} at `{}`", profile, logical);
+            }
+        }
+    }
+
+    #[test]
+    fn logical_values_round_trip_where_the_curve_is_not_compressed() {
+        let identity: Calibration = calibration(&[], None, None);
+        let steep: Calibration = calibration(&[[0, 0], [50, 100]], None, None);
+
+        for logical in 0..=100 {
+            assert_eq!(identity.to_logical(identity.to_device(logical)), logical);
+        }
+        for logical in 0..=50 {
+            assert_eq!(steep.to_logical(steep.to_device(logical)), logical);
+        }
+    }
+
+    #[test]
+    fn mapping_is_monotonic_and_stays_in_the_caps() {
+        for profile in profiles() {
+            let (min, max): (u32, u32) = profile.range();
+            for value in 1..=100 {
+                assert!(profile.to_device(value) >= profile.to_device(value - 1), "{:?} at `{}`", profile, value);
+                assert!(profile.to_logical(value) >= profile.to_logical(value - 1), "{:?} at `{}`", profile, value);
+                assert!((min..=max).contains(&profile.to_device(value)), "{:?} at `{}`", profile, value);
+            }
+        }
+    }
+
+    #[test]
+    fn device_values_outside_the_caps_read_as_the_ends() {
+        let profile: Calibration = calibration(&[], Some(10), Some(80));
+
+        assert_eq!(profile.to_logical(0), 0);
+        assert_eq!(profile.to_logical(95), 100);
+    }
+
+    #[test]
+    fn flat_parts_read_back_as_their_lowest_logical_value() {
+        let profile: Calibration = calibration(&[[0, 10], [30, 10], [100, 100]], None, None);
+
+        assert_eq!(profile.to_device(20), 10);
+        assert_eq!(profile.to_logical(10), 0);
+    }
+}
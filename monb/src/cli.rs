@@ -13,9 +13,18 @@ pub mod regexes {
     pub const PERCENTAGE_GROUP: &'static str    = "percentage";
     pub const MIN_GROUP: &'static str           = "min";
     pub const MAX_GROUP: &'static str           = "max";
+    pub const MULTIPLY_GROUP: &'static str      = "multiply";
+    pub const FACTOR_GROUP: &'static str        = "factor";
+    pub const STEPS_ACTION_GROUP: &'static str  = "steps_action";
+    pub const STEPS_GROUP: &'static str         = "steps";
+    pub const COMPARISON_GROUP: &'static str    = "comparison";
+    pub const CONDITION_GROUP: &'static str     = "condition";
+
+    // index, range, `@serial` or identifier
+    const SCOPE_ITEM: &'static str = r"(?:[0-9]+(?:-[0-9]+)?|@[^:,\s]+|[a-z_\\][^:,\s]*)";
 
     const BARG: Lazy<String> = Lazy::new(|| {
-        r"(?i)^/?(?:(?P<{{SCOPE_GROUP}}>[0-9]+|\*|all):)?(?P<{{VALUE_GROUP}}>(?P<{{ACTION_GROUP}}>\+|-)?(?:(?:(?P<{{BRIGHTNESS_GROUP}}>[0-9]+)(?:/(?P<{{DENOMINATOR_GROUP}}>[0-9]+))?(?P<{{PERCENTAGE_GROUP}}>%)?)|(?P<{{MIN_GROUP}}>{{MIN}})|(?P<{{MAX_GROUP}}>{{MAX}})))?$"
+        r"(?i)^/?(?:(?P<{{SCOPE_GROUP}}>\*|all|(?:!|\^|\*-)?{{SCOPE_ITEM}}(?:,{{SCOPE_ITEM}})*):)?(?P<{{VALUE_GROUP}}>(?P<{{STEPS_ACTION_GROUP}}>toggle|cycle)\(\s*(?P<{{STEPS_GROUP}}>[0-9]+(?:\s*,\s*[0-9]+)*)\s*\)|(?P<{{MULTIPLY_GROUP}}>x|\*)(?P<{{FACTOR_GROUP}}>[0-9]+(?:\.[0-9]*)?|\.[0-9]+)|(?P<{{ACTION_GROUP}}>\+|-)?(?:(?:(?P<{{BRIGHTNESS_GROUP}}>[0-9]+)(?:/(?P<{{DENOMINATOR_GROUP}}>[0-9]+))?(?P<{{PERCENTAGE_GROUP}}>%)?)|(?P<{{MIN_GROUP}}>{{MIN}})|(?P<{{MAX_GROUP}}>{{MAX}})))?(?:\s+if\s*(?P<{{COMPARISON_GROUP}}><=|>=|<|>|=)\s*(?P<{{CONDITION_GROUP}}>[0-9]+))?$"
         /* more readable formatted version:
             (?x) # verbose mode
             (?i) # case insensitive mode
@@ -23,10 +32,24 @@ pub mod regexes {
 
             /?
             (?:
-                (? <{{SCOPE_GROUP}}> [0-9]+|\*|all) :
+                (? <{{SCOPE_GROUP}}>
+                    \*|all
+                    |
+                    (?: !|\^|\*- )? {{SCOPE_ITEM}} (?: , {{SCOPE_ITEM}} )*
+                ) :
             )?
 
             (? <{{VALUE_GROUP}}>
+                (? <{{STEPS_ACTION_GROUP}}> toggle|cycle)
+                \( \s* (? <{{STEPS_GROUP}}> [0-9]+ (?: \s* , \s* [0-9]+ )* ) \s* \)
+
+                |
+
+                (? <{{MULTIPLY_GROUP}}> x|\*)
+                (? <{{FACTOR_GROUP}}> [0-9]+(?:\.[0-9]*)?|\.[0-9]+)
+
+                |
+
                 (? <{{ACTION_GROUP}}> \+|-)?
 
                 (?:
@@ -50,8 +73,16 @@ pub mod regexes {
                 )
             )?
 
+            (?:
+                \s+ if \s*
+                (? <{{COMPARISON_GROUP}}> <=|>=|<|>|=)
+                \s*
+                (? <{{CONDITION_GROUP}}> [0-9]+)
+            )?
+
             $"
             */
+            .replace(r"{{SCOPE_ITEM}}", SCOPE_ITEM)
             .replace(r"{{SCOPE_GROUP}}", SCOPE_GROUP)
             .replace(r"{{VALUE_GROUP}}", VALUE_GROUP)
             .replace(r"{{ACTION_GROUP}}", ACTION_GROUP)
@@ -60,10 +91,19 @@ pub mod regexes {
             .replace(r"{{PERCENTAGE_GROUP}}", PERCENTAGE_GROUP)
             .replace(r"{{MIN_GROUP}}", MIN_GROUP)
             .replace(r"{{MAX_GROUP}}", MAX_GROUP)
+            .replace(r"{{MULTIPLY_GROUP}}", MULTIPLY_GROUP)
+            .replace(r"{{FACTOR_GROUP}}", FACTOR_GROUP)
+            .replace(r"{{STEPS_ACTION_GROUP}}", STEPS_ACTION_GROUP)
+            .replace(r"{{STEPS_GROUP}}", STEPS_GROUP)
+            .replace(r"{{COMPARISON_GROUP}}", COMPARISON_GROUP)
+            .replace(r"{{CONDITION_GROUP}}", CONDITION_GROUP)
             .replace(r"{{MIN}}", MIN_NAMES.join("|").as_str())
             .replace(r"{{MAX}}", MAX_NAMES.join("|").as_str())
     });
     pub const BARG_REGEX: Lazy<Regex> = Lazy::new(|| { Regex::new(&BARG).expect("Invalid `brightness string` regex`") });
+
+    const FADE_DURATION: &'static str = "(?i)^([0-9]+)(ms|s|sec|m|min)?$";
+    pub const FADE_DURATION_REGEX: Lazy<Regex> = Lazy::new(|| { Regex::new(FADE_DURATION).expect("Invalid `fade duration` regex") });
 }
 
 
@@ -75,6 +115,13 @@ pub mod params {
     use once_cell::sync::Lazy;
     use strum::IntoEnumIterator;
 
+    use crate::backend::{BackendType, MonitorKind};
+    use crate::config::Config;
+    use crate::fade::{Easing, FadeInfo};
+    use crate::feature::Feature;
+    use crate::output::OutputFormat;
+    use crate::template::{Placeholder, Template};
+    use crate::statusbar::StatusBar;
     use crate::progressbar::{ProgressBarInfo, ProgressBarType};
     use crate::parser::parse_bargs;
 
@@ -96,6 +143,20 @@ pub mod params {
     pub const FLAG_VERBOSE_LONG_NAME: &'static str  = "verbose";
     pub const FLAG_VERBOSE_HELP: &'static str       = "Prints debug information during execution.";
 
+    pub const FLAG_RAW_ID: &'static str             = "FLAG_RAW";
+    pub const FLAG_RAW_NAME: &'static str           = "RAW";
+    pub const FLAG_RAW_LONG_NAME: &'static str      = "raw";
+    pub const FLAG_RAW_HELP: &'static str           = "Uses raw device brightness values instead of percentages (sysfs and ddc backends only).";
+
+    pub const FLAG_PERCEPTUAL_ID: &'static str          = "FLAG_PERCEPTUAL";
+    pub const FLAG_PERCEPTUAL_NAME: &'static str        = "PERCEPTUAL";
+    pub const FLAG_PERCEPTUAL_SHORT_NAME: char          = 'p';
+    pub const FLAG_PERCEPTUAL_LONG_NAME: &'static str   = "perceptual";
+    pub const FLAG_PERCEPTUAL_HELP: &'static str        = concat!(
+        "Uses a perceptual lightness curve (CIE L*) for brightness values, relative steps, conditions and progressbars.", '\n',
+        "Undo and restore still set the saved device values."
+    );
+
     pub const ARG_BARGS_ID: &'static str            = "ARG_BARGS";
     pub const ARG_BARGS_NAME: &'static str          = "BRIGHTNESS ARGUMENTS";
     pub static ARG_BARGS_HELP: Lazy<String>         = Lazy::new(|| {
@@ -120,8 +181,11 @@ pub mod params {
             "Getter arguments:", '\n',
                 '\t', "Format: <BScope>: This type of argument is very simple.", '\n', '\n',
                 '\t', "It just has a scope and it will return the brightness value of a monitor.", '\n',
-                '\t', "Scope determines which monitors will be selected. Scope values include unsigned integers (indexed scope) or", '\n',
-                '\t', "an asterisk (*, global scope). If a global scope is present then all indexed scopes will be ignored, because", '\n',
+                '\t', "Scope determines which monitors will be selected. Scope values include unsigned integers (indexed scope),", '\n',
+                '\t', "monitor identifiers (DP-2, intel_backlight), EDID serials prefixed with @ (@ABC123) or an asterisk", '\n',
+                '\t', "(*, global scope). Ranges (1-3), lists (1,3,5) and exclusions (!2, ^2 or *-2, every monitor except 2)", '\n',
+                '\t', "combine them. `internal` selects built-in panels and `external` all other displays.", '\n',
+                '\t', "If a global scope is present then all indexed scopes will be ignored, because", '\n',
                 '\t', "they are included into the global scope.", '\n', '\n',
             "Setter arguments:", '\n',
                 '\t', "Format: (<BScope>:)?<BValue>", '\n', '\n',
@@ -142,7 +206,18 @@ pub mod params {
                 '\t', "by brightness action. Brightness action can be '+', '-' or empty (setter action).", '\n',
                 '\t', "Increment action (+) will increment the brightness by some brightness value and", '\n',
                 '\t', "decrement action (-) will decrement it. If the action isn't provided it will", '\n',
-                '\t', "default to setter action that will set the monitor brightness to desired brightness value."
+                '\t', "default to setter action that will set the monitor brightness to desired brightness value.", '\n',
+                '\t', "Increments and decrements ending with % (+10%, -20%) are relative to the current value, at least 1.", '\n',
+                '\t', "Multipliers (x1.5 or *0.5) multiply the current value. Both round to the nearest integer and clamp", '\n',
+                '\t', "the result to the valid range.", '\n',
+                '\t', "Toggles (toggle(20,80)) switch between two values and cycles (cycle(10,40,70,100)) step through", '\n',
+                '\t', "a list, both based on the current value. Values within 2% of the current one count as equal, codes have to match exactly.", '\n',
+                '\t', "Setters can end with a condition (if <30, if >=20, <, <=, >, >=, =) checked against the current", '\n',
+                '\t', "value, the monitor is skipped if it isn't met. Relative changes stop at the condition bound.", '\n',
+                '\t', "Conditions use the same scale as the values, with --perceptual the perceptual percentage.", '\n', '\n',
+
+                '\t', "If setter scopes overlap, single monitors win over lists, ranges and kinds, which win over exclusions,", '\n',
+                '\t', "which win over the global scope. Between equally specific scopes the first argument wins."
         );
 
         return format!("{}{}{}", help_part_1, help_part_2, help_part_3);
@@ -180,51 +255,373 @@ pub mod params {
         )
     });
 
+    pub const ARG_BACKEND_ID: &'static str          = "ARG_BACKEND";
+    pub const ARG_BACKEND_NAME: &'static str        = "BACKEND";
+    pub const ARG_BACKEND_SHORT_NAME: char          = 'b';
+    pub const ARG_BACKEND_LONG_NAME: &'static str   = "backend";
+    pub const ARG_BACKEND_ENV: &'static str         = "MONB_BACKEND";
+    pub static ARG_BACKEND_HELP: Lazy<String>       = Lazy::new(|| {
+
+        let backend_enum_variants: String = BackendType::iter()
+            .map(|x| format!("`{}`", x.to_string()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!("{}\n{} [ {} ]\n{}",
+            "Sets the brightness backend used to find and control monitors.",
+            "Possible backends:",
+            backend_enum_variants,
+            "The `fake` backend reads virtual monitors from the JSON file set in `MONB_FAKE_FILE`."
+        )
+    });
+
+    pub const ARG_FEATURE_ID: &'static str          = "ARG_FEATURE";
+    pub const ARG_FEATURE_NAME: &'static str        = "FEATURE";
+    pub const ARG_FEATURE_SHORT_NAME: char          = 'f';
+    pub const ARG_FEATURE_LONG_NAME: &'static str   = "feature";
+    pub static ARG_FEATURE_HELP: Lazy<String>       = Lazy::new(|| {
+
+        let feature_enum_variants: String = Feature::iter()
+            .map(|x| format!("`{}`", x.to_string()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!("{}\n{} [ {} ]\n{}",
+            "Sets the monitor control that brightness arguments are applied to.",
+            "Possible features:",
+            feature_enum_variants,
+            "Input source and power mode use raw VCP codes instead of percentages."
+        )
+    });
+
+    pub const ARG_OUTPUT_ID: &'static str           = "ARG_OUTPUT";
+    pub const ARG_OUTPUT_NAME: &'static str         = "FORMAT";
+    pub const ARG_OUTPUT_SHORT_NAME: char           = 'o';
+    pub const ARG_OUTPUT_LONG_NAME: &'static str    = "output";
+    pub static ARG_OUTPUT_HELP: Lazy<String>        = Lazy::new(|| {
+
+        let output_enum_variants: String = OutputFormat::iter()
+            .map(|x| format!("`{}`", x.to_string()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!("{}\n{} [ {} ]\n{}",
+            "Sets the format of getter and setter results.",
+            "Possible formats:",
+            output_enum_variants,
+            "`json` and `tsv` print one record per monitor to stdout and move logs to stderr."
+        )
+    });
+
+    pub const ARG_FORMAT_ID: &'static str           = "ARG_FORMAT";
+    pub const ARG_FORMAT_NAME: &'static str         = "TEMPLATE";
+    pub const ARG_FORMAT_LONG_NAME: &'static str    = "format";
+    pub static ARG_FORMAT_HELP: Lazy<String>        = Lazy::new(|| {
+
+        let placeholders: String = Placeholder::iter()
+            .map(|x| format!("`{{{}}}`", x.to_string()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!("{}\n{} [ {} ]\n{}\n{}",
+            "Prints getter lines with a template instead of the default line, e.g. `{name}: {percent}% {bar}`.",
+            "Possible placeholders:",
+            placeholders,
+            "`{value}` is the device value (the raw one with --raw), `{percent}` the percentage shown by the progressbar.",
+            "`{{` and `}}` are literal braces. Lines are printed to stdout without a prefix, logs are moved to stderr."
+        )
+    });
+
+    pub const ARG_FADE_ID: &'static str             = "ARG_FADE";
+    pub const ARG_FADE_NAME: &'static str           = "DURATION";
+    pub const ARG_FADE_LONG_NAME: &'static str      = "fade";
+    pub const ARG_FADE_HELP: &'static str           = concat!(
+        "Fades from the current to the desired value over time instead of jumping.", '\n',
+        "Format: `[POSITIVE INTEGER][UNIT]`, UNIT includes `ms`, `s`, `sec`, `m`, `min`.", '\n',
+        "If the UNIT is not provided, default unit (seconds) will be used.", '\n',
+        "All monitors fade in parallel and finish at the same moment. Ctrl-C stops the fade at the reached value."
+    );
+
+    pub const ARG_EASING_ID: &'static str           = "ARG_EASING";
+    pub const ARG_EASING_NAME: &'static str         = "EASING";
+    pub const ARG_EASING_LONG_NAME: &'static str    = "easing";
+    pub static ARG_EASING_HELP: Lazy<String>        = Lazy::new(|| {
+
+        let easing_enum_variants: String = Easing::iter()
+            .map(|x| format!("`{}`", x.to_string()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!("{}\n{} [ {} ]",
+            "Sets the curve used by `--fade`.",
+            "Possible curves:",
+            easing_enum_variants
+        )
+    });
+
+    pub const ARG_CONFIG_ID: &'static str           = "ARG_CONFIG";
+    pub const ARG_CONFIG_NAME: &'static str         = "PATH";
+    pub const ARG_CONFIG_SHORT_NAME: char           = 'c';
+    pub const ARG_CONFIG_LONG_NAME: &'static str    = "config";
+    pub const ARG_CONFIG_ENV: &'static str          = "MONB_CONFIG";
+    pub const ARG_CONFIG_HELP: &'static str         = concat!(
+        "Sets the configuration file.", '\n',
+        "Defaults to `monb/config.toml` inside the user configuration directory (`$XDG_CONFIG_HOME` on linux).", '\n',
+        "The configuration holds per-monitor calibration profiles and monitor aliases."
+    );
+
+    pub const SUBCOMMAND_LIST_NAME: &'static str    = "list";
+    pub const SUBCOMMAND_LIST_HELP: &'static str    = concat!(
+        "Lists all monitors with their index, backend, identifiers and EDID metadata", '\n',
+        "(manufacturer, product code, model, serial, physical size and manufacture year)."
+    );
+
+    pub const SUBCOMMAND_SCENE_NAME: &'static str       = "scene";
+    pub const SUBCOMMAND_SCENE_HELP: &'static str       = concat!(
+        "Applies a scene, a named list of brightness arguments from the `scenes` table of the configuration file.", '\n',
+        "Scenes are validated like brightness arguments, errors point to the line of the scene."
+    );
+    pub const SUBCOMMAND_SCENE_SAVE_NAME: &'static str  = "save";
+    pub const SUBCOMMAND_SCENE_SAVE_HELP: &'static str  = concat!(
+        "Saves the current brightness of every monitor as a scene, replacing a scene with the same name.", '\n',
+        "Monitors are saved by their most specific identifier, the rest of the configuration file is kept as it is."
+    );
+    pub const ARG_SCENE_ID: &'static str                = "ARG_SCENE";
+    pub const ARG_SCENE_NAME: &'static str              = "NAME";
+    pub const ARG_SCENE_HELP: &'static str              = "Name of the scene";
+
+    pub const SUBCOMMAND_BAR_NAME: &'static str         = "bar";
+    pub const SUBCOMMAND_BAR_HELP: &'static str         = concat!(
+        "Prints brightness in the format of a status bar: waybar JSON (text, tooltip, percentage, class),", '\n',
+        "i3blocks JSON (full and short text, needs `format=json`) or a polybar line.", '\n',
+        "Scroll events change the brightness by a step before printing, i3blocks scroll buttons are read from `BLOCK_BUTTON`."
+    );
+    pub const ARG_BAR_ID: &'static str                  = "ARG_BAR";
+    pub const ARG_BAR_NAME: &'static str                = "BAR";
+    pub static ARG_BAR_HELP: Lazy<String>               = Lazy::new(|| {
+        let bar_enum_variants: String = StatusBar::iter()
+            .map(|x| format!("`{}`", x.to_string()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!("Status bar the output is formatted for. Possible bars: [ {} ]", bar_enum_variants)
+    });
+    pub const ARG_BAR_SCOPE_ID: &'static str            = "ARG_BAR_SCOPE";
+    pub const ARG_BAR_SCOPE_NAME: &'static str          = "SCOPE";
+    pub const ARG_BAR_SCOPE_HELP: &'static str          = "Monitors to show, any scope (`1`, `DP-1`, `external`, `1-2`), all monitors by default";
+    pub const ARG_SCROLL_ID: &'static str               = "ARG_SCROLL";
+    pub const ARG_SCROLL_NAME: &'static str             = "DIRECTION";
+    pub const ARG_SCROLL_LONG_NAME: &'static str        = "scroll";
+    pub const ARG_SCROLL_HELP: &'static str             = "Increments (`up`) or decrements (`down`) the brightness by the step before printing";
+    pub const ARG_STEP_ID: &'static str                 = "ARG_STEP";
+    pub const ARG_STEP_NAME: &'static str               = "STEP";
+    pub const ARG_STEP_LONG_NAME: &'static str          = "step";
+    pub const ARG_STEP_HELP: &'static str               = concatcp!("Brightness step of a scroll event, ", crate::statusbar::STEP_DEFAULT, " by default");
+    pub const FLAG_FOLLOW_ID: &'static str              = "FLAG_FOLLOW";
+    pub const FLAG_FOLLOW_LONG_NAME: &'static str       = "follow";
+    pub const FLAG_FOLLOW_HELP: &'static str            = "Keeps running and prints a new line whenever the brightness of a shown monitor changes";
+
+    pub const SUBCOMMAND_WATCH_NAME: &'static str       = "watch";
+    pub const SUBCOMMAND_WATCH_HELP: &'static str       = concat!(
+        "Shows progressbars of the monitors and redraws them in place until Ctrl-C.", '\n',
+        "Monitors whose value changed since the last frame are highlighted."
+    );
+    pub const ARG_WATCH_SCOPE_ID: &'static str          = "ARG_WATCH_SCOPE";
+    pub const ARG_WATCH_SCOPE_NAME: &'static str        = "SCOPE";
+    pub const ARG_WATCH_SCOPE_HELP: &'static str        = "Monitors to watch, any scope (`1`, `DP-1`, `external`, `1-2`), all monitors by default";
+    pub const ARG_INTERVAL_ID: &'static str             = "ARG_INTERVAL";
+    pub const ARG_INTERVAL_NAME: &'static str           = "DURATION";
+    pub const ARG_INTERVAL_LONG_NAME: &'static str      = "interval";
+    pub const ARG_INTERVAL_HELP: &'static str           = concat!(
+        "Time between two readings of the monitors, 1 second by default.", '\n',
+        "Format: `[POSITIVE INTEGER][UNIT]`, UNIT includes `ms`, `s`, `sec`, `m`, `min`."
+    );
+
+    pub const SUBCOMMAND_INTERACTIVE_NAME: &'static str = "interactive";
+    pub const SUBCOMMAND_INTERACTIVE_HELP: &'static str = concat!(
+        "Lists all monitors with their progressbars and changes their brightness with the keyboard.", '\n',
+        "Up and down select a monitor, left and right change its brightness by 5 (1 with shift), Home and End set the minimum and maximum,", '\n',
+        "digits followed by Enter set an exact value, `a` links all monitors so every change applies to all of them, `q` quits."
+    );
+
+    pub const SUBCOMMAND_UNDO_NAME: &'static str    = "undo";
+    pub const SUBCOMMAND_UNDO_HELP: &'static str    = concat!(
+        "Sets monitors changed by the last command back to their previous values.", '\n',
+        "Running it again redoes the change."
+    );
+
+    pub const SUBCOMMAND_RESTORE_NAME: &'static str = "restore";
+    pub const SUBCOMMAND_RESTORE_HELP: &'static str = concat!(
+        "Sets every monitor to its last known value, e.g. at login.", '\n',
+        "Values are saved in `monb/state.json` inside the user state directory, or in `MONB_STATE_FILE`."
+    );
+
     #[derive(Debug, PartialEq, Eq, Hash, Clone)]
     pub enum BScope {
         Global,
-        Index(usize)
+        Index(usize),
+
+        // monitor identity or connector, `DP-2`
+        Id(String),
+
+        // EDID serial, `@ABC123`
+        Serial(String),
+
+        // `internal` or `external`
+        Kind(MonitorKind),
+
+        // inclusive, `1-3`
+        Range(usize, usize),
+
+        // `1,3,5`, items are single scopes or ranges
+        List(Vec<BScope>),
+
+        // every monitor except the listed ones, `!2`, `^2` or `*-2`
+        Except(Vec<BScope>),
+
+        // name from the `[aliases]` configuration table and the scope it stands for
+        Alias(String, Box<BScope>)
     }
 
     impl ToString for BScope {
         fn to_string(&self) -> String {
-            match *self {
+            let join = |items: &Vec<BScope>| items.iter().map(|item| item.to_string()).collect::<Vec<String>>().join(",");
+
+            match self {
                 Self::Global => "*".to_string(),
-                Self::Index(i) => i.to_string()
+                Self::Index(i) => i.to_string(),
+                Self::Id(id) => id.clone(),
+                Self::Serial(serial) => format!("@{}", serial),
+                Self::Kind(kind) => kind.to_string(),
+                Self::Range(from, to) => format!("{}-{}", from, to),
+                Self::List(items) => join(items),
+                Self::Except(items) => format!("!{}", join(items)),
+                Self::Alias(name, _) => name.clone()
             }
         }
     }
 
+    impl BScope {
+        /// When scopes overlap, the monitor takes the argument with the lowest precedence value:
+        /// single monitors, then lists, ranges and kinds, then exclusions and the global scope last.
+        /// Arguments with the same precedence keep their command line order, the first one wins.
+        pub fn precedence(&self) -> usize {
+            match self {
+                Self::Index(_) | Self::Id(_) | Self::Serial(_) => 0,
+                Self::Range(_, _) | Self::List(_) | Self::Kind(_) => 1,
+                Self::Except(_) => 2,
+                Self::Global => 3,
+                Self::Alias(_, scope) => scope.precedence()
+            }
+        }
+
+        /// Replaces identifiers that name an alias with the aliased scope, aliases win over monitor identifiers.
+        pub fn expand_aliases(self, config: &Config) -> Self {
+            match self {
+                Self::Id(name) => match config.alias(&name) {
+                    Some(alias) => Self::Alias(name, Box::new(alias.0.clone())),
+                    None => Self::Id(name)
+                },
+                Self::List(items) => Self::List(items.into_iter().map(|item| item.expand_aliases(config)).collect()),
+                Self::Except(items) => Self::Except(items.into_iter().map(|item| item.expand_aliases(config)).collect()),
+                scope => scope
+            }
+        }
+
+        fn parse_index(s: &str) -> Result<usize, String> {
+            match s.parse::<usize>() {
+                Ok(number) => Ok(number),
+                Err(err) => match err.kind() {
+                    IntErrorKind::PosOverflow => Err(format!("Index `{}` is too big", s)),
+                    _ => Err(format!("Index `{}` is not a number", s))
+                }
+            }
+        }
+
+        // single scope or a range, everything that can be a list item
+        fn parse_item(s: &str) -> Result<Self, String> {
+            if let Some(serial) = s.strip_prefix('@') {
+                return match serial.is_empty() {
+                    true => Err("Serial scope `@` is missing the serial".into()),
+                    false => Ok(Self::Serial(serial.to_string()))
+                };
+            }
+
+            // anything that doesn't start with a digit is an identifier, the index is the fallback
+            if !s.starts_with(|c: char| c.is_ascii_digit()) {
+                return match s.to_lowercase().as_str() {
+                    "internal" => Ok(Self::Kind(MonitorKind::Internal)),
+                    "external" => Ok(Self::Kind(MonitorKind::External)),
+                    "" => Err("Scope list contains an empty item".into()),
+                    "*" => Err("Global scope `*` can't be a part of a list".into()),
+                    _ => Ok(Self::Id(s.to_string()))
+                };
+            }
+
+            match s.split_once('-') {
+                None => Ok(Self::Index(Self::parse_index(s)?)),
+                Some((from, to)) => {
+                    let (from, to) = (Self::parse_index(from)?, Self::parse_index(to)?);
+                    match from <= to {
+                        true => Ok(Self::Range(from, to)),
+                        false => Err(format!("Range `{}-{}` is reversed, did you mean `{}-{}`?", from, to, to, from))
+                    }
+                }
+            }
+        }
+
+        fn parse_list(s: &str) -> Result<Vec<Self>, String> {
+            s.split(',').map(|item| Self::parse_item(item.trim())).collect()
+        }
+    }
+
     impl FromStr for BScope {
         type Err = String;
 
         fn from_str(s: &str) -> Result<Self, Self::Err> {
-            match s.to_lowercase().trim() {
-                "*" | "all" => Ok(Self::Global),
-                potential_number => match potential_number.parse::<usize>() {
-                    Ok(number) => Ok(BScope::Index(number)),
-                    Err(err) => match err.kind() {
-                        IntErrorKind::PosOverflow => Err(format!("Index `{}` is too big", potential_number)),
-                        _ => Err(format!("Index `{}` is not a number", potential_number))
-                    }
-                }
+            let s: &str = s.trim();
+
+            if s == "*" || s.eq_ignore_ascii_case("all") {
+                return Ok(Self::Global);
+            }
+
+            if let Some(excluded) = s.strip_prefix('!').or_else(|| s.strip_prefix('^')).or_else(|| s.strip_prefix("*-")) {
+                return Ok(Self::Except(Self::parse_list(excluded)?));
+            }
+
+            let mut items: Vec<Self> = Self::parse_list(s)?;
+            match items.len() {
+                1 => Ok(items.remove(0)),
+                _ => Ok(Self::List(items))
             }
         }
     }
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
     pub enum BAction {
         Set,
         Get,
         Inc,
-        Dec
+        Dec,
+
+        // current value multiplied by `BValue::factor`
+        Mul,
+
+        // `BValue::brightness` percent of the current value
+        IncPercent,
+        DecPercent,
+
+        // next value from `BValue::steps`, based on the current value
+        Toggle,
+        Cycle
     }
 
     impl Into<&'static str> for &BAction {
         fn into(self) -> &'static str {
             match self {
-                BAction::Dec => "-",
-                BAction::Inc => "+",
+                BAction::Dec | BAction::DecPercent => "-",
+                BAction::Inc | BAction::IncPercent => "+",
+                BAction::Mul => "x",
                 _ => ""
             }
         }
@@ -245,39 +642,167 @@ pub mod params {
     #[derive(Debug, PartialEq)]
     pub struct BValue {
         pub action: BAction,
-        pub brightness: Option<usize>
+        pub brightness: Option<usize>,
+
+        // only used by `BAction::Mul`
+        pub factor: Option<f64>,
+
+        // only used by `BAction::Toggle` and `BAction::Cycle`
+        pub steps: Vec<usize>
+    }
+
+    impl BValue {
+        pub fn get() -> Self {
+            Self { action: BAction::Get, brightness: None, factor: None, steps: vec![] }
+        }
+
+        /// `Set`, `Inc`, `Dec` and their percentage variants.
+        pub fn new(action: BAction, brightness: usize) -> Self {
+            Self { action, brightness: Some(brightness), factor: None, steps: vec![] }
+        }
+
+        pub fn multiply(factor: f64) -> Self {
+            Self { action: BAction::Mul, brightness: None, factor: Some(factor), steps: vec![] }
+        }
+
+        /// `Toggle` and `Cycle`.
+        pub fn steps(action: BAction, steps: Vec<usize>) -> Self {
+            Self { action, brightness: None, factor: None, steps }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum BComparison {
+        Less,
+        LessOrEqual,
+        Greater,
+        GreaterOrEqual,
+        Equal
+    }
+
+    impl Into<&'static str> for &BComparison {
+        fn into(self) -> &'static str {
+            match self {
+                BComparison::Less => "<",
+                BComparison::LessOrEqual => "<=",
+                BComparison::Greater => ">",
+                BComparison::GreaterOrEqual => ">=",
+                BComparison::Equal => "="
+            }
+        }
+    }
+
+    impl FromStr for BComparison {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.trim() {
+                "<" => Ok(Self::Less),
+                "<=" => Ok(Self::LessOrEqual),
+                ">" => Ok(Self::Greater),
+                ">=" => Ok(Self::GreaterOrEqual),
+                "=" => Ok(Self::Equal),
+                _ => Err(format!("Comparison `{}` is not valid", s))
+            }
+        }
+    }
+
+    /// Guard checked against the current value before a setter is applied, `if <30`.
+    #[derive(Debug, PartialEq)]
+    pub struct BCondition {
+        pub comparison: BComparison,
+        pub brightness: usize
+    }
+
+    impl BCondition {
+        pub fn is_met(&self, value: u32) -> bool {
+            let bound: u64 = self.brightness as u64;
+            let value: u64 = value as u64;
+
+            match self.comparison {
+                BComparison::Less => value < bound,
+                BComparison::LessOrEqual => value <= bound,
+                BComparison::Greater => value > bound,
+                BComparison::GreaterOrEqual => value >= bound,
+                BComparison::Equal => value == bound
+            }
+        }
+
+        /// Relative steps stop at the bound, so `-10 if >20` never dims below 20.
+        pub fn clamp(&self, value: u32) -> u32 {
+            let bound: u32 = self.brightness.min(u32::MAX as usize) as u32;
+
+            match self.comparison {
+                BComparison::Greater | BComparison::GreaterOrEqual => value.max(bound),
+                BComparison::Less | BComparison::LessOrEqual => value.min(bound),
+                BComparison::Equal => value
+            }
+        }
+    }
+
+    impl ToString for BCondition {
+        fn to_string(&self) -> String {
+            let comparison: &'static str = (&self.comparison).into();
+            return format!("{}{}", comparison, self.brightness);
+        }
     }
 
     #[derive(Debug, PartialEq)]
     pub struct BArg {
         pub scope: BScope,
-        pub value: BValue
+        pub value: BValue,
+        pub condition: Option<BCondition>
     }
 
     impl ToString for BArg {
         fn to_string(&self) -> String {
             let prefix: &'static str = (&self.value.action).into();
             let scope: String = self.scope.to_string();
-            return format!("{}:{}{}",
+
+            let condition: String = match &self.condition {
+                Some(c) => format!(" if {}", c.to_string()),
+                None => "".into()
+            };
+
+            if let BAction::Toggle | BAction::Cycle = self.value.action {
+                let steps: Vec<String> = self.value.steps.iter().map(|step| step.to_string()).collect();
+                return format!("{}:{}({}){}",
+                    scope,
+                    crate::ternary_operator!(self.value.action == BAction::Toggle, "toggle", "cycle"),
+                    steps.join(","),
+                    condition
+                );
+            }
+
+            let suffix: &'static str = match self.value.action {
+                BAction::IncPercent | BAction::DecPercent => "%",
+                _ => ""
+            };
+
+            return format!("{}:{}{}{}{}",
                 scope,
                 prefix,
-                match self.value.brightness {
-                    Some(v) => v.to_string(),
-                    None => "".into()
-                }
+                match (self.value.brightness, self.value.factor) {
+                    (Some(v), _) => v.to_string(),
+                    (None, Some(f)) => f.to_string(),
+                    (None, None) => "".into()
+                },
+                suffix,
+                condition
             );
         }
     }
 
+    impl BArg {
+        /// Argument without a condition.
+        pub fn new(scope: BScope, value: BValue) -> Self {
+            Self { scope, value, condition: None }
+        }
+    }
+
     impl Default for BArg {
         fn default() -> Self {
-            Self {
-                scope: BScope::Global,
-                value: BValue {
-                    action: BAction::Get,
-                    brightness: None
-                }
-            }
+            Self::new(BScope::Global, BValue::get())
         }
     }
 
@@ -304,18 +829,53 @@ pub mod params {
     #[derive(Debug)]
     pub struct Arguments {
         pub flag_zero: bool,
+        pub flag_raw: bool,
+        pub flag_perceptual: bool,
+        pub feature: Feature,
+        pub backend: BackendType,
         pub progressbar_info: ProgressBarInfo,
-        pub bargs: BArgs
+        pub fade_info: FadeInfo,
+        pub output: OutputFormat,
+        pub template: Option<Template>,
+        pub config: Config,
+        pub bargs: BArgs,
+
+        // largest brightness value an argument can hold, scenes are parsed with it too
+        pub value_max: usize
     }
 
     impl TryFrom<&ArgMatches> for Arguments {
         type Error = String;
 
         fn try_from(value: &ArgMatches) -> Result<Self, Self::Error> {
+            let flag_raw: bool = value.get_flag(FLAG_RAW_ID);
+            let feature: Feature = Feature::try_from(value.get_one::<String>(ARG_FEATURE_ID))?;
+            let config: Config = Config::load(value.get_one::<String>(ARG_CONFIG_ID))?;
+
+            // templates are checked before any device is touched
+            let output: OutputFormat = OutputFormat::try_from(value.get_one::<String>(ARG_OUTPUT_ID))?;
+            let template: Option<Template> = match value.get_one::<String>(ARG_FORMAT_ID) {
+                None => None,
+                Some(_) if output.is_structured() => return Err(format!("`--{}` only applies to text output, it can't be combined with `--{} {}`", ARG_FORMAT_LONG_NAME, ARG_OUTPUT_LONG_NAME, output)),
+                Some(t) => Some(Template::from_str(t)?)
+            };
+
+            // raw values and codes are clamped by the device itself
+            let value_max: usize = crate::ternary_operator!(flag_raw || !feature.is_continuous(), u32::MAX as usize, 100);
+
             Ok(Arguments {
                 flag_zero: value.get_flag(FLAG_ZERO_ID),
+                flag_raw,
+                flag_perceptual: value.get_flag(FLAG_PERCEPTUAL_ID) || config.perceptual,
+                feature,
+                backend: BackendType::try_from(value.get_one::<String>(ARG_BACKEND_ID))?,
                 progressbar_info: ProgressBarInfo::try_from(value)?,
-                bargs: parse_bargs(value.get_many::<String>(ARG_BARGS_ID))?
+                fade_info: FadeInfo::try_from(value)?,
+                output,
+                template,
+                bargs: parse_bargs(value.try_get_many::<String>(ARG_BARGS_ID).unwrap_or(None), value_max, feature, &config)?, // subcommands have no brightness arguments
+                config,
+                value_max
             })
         }
     }
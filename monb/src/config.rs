@@ -0,0 +1,205 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use log::debug;
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::calibration::Calibration;
+use crate::limits::Limits;
+use crate::cli::params::BScope;
+
+const CONFIG_DIRECTORY: &'static str = "monb";
+const CONFIG_FILE: &'static str = "config.toml";
+
+// limits key applying to every monitor
+pub const GLOBAL_LIMITS: &'static str = "*";
+
+// Example configuration file:
+// perceptual = true
+//
+// [calibration."DP-1"]
+// points = [[0, 5], [50, 30], [100, 90]]
+//
+// [calibration.intel_backlight]
+// min = 5
+//
+// [limits."*"]
+// max = 90
+//
+// [limits."DP-1"]
+// min = 5
+//
+// [aliases]
+// left = "DP-2"
+// center = "@ABC123"
+// laptop = "internal"
+// desk = ["DP-2", "@ABC123"]
+//
+// [scenes]
+// night = ["internal:20", "external:5"]
+// presentation = ["1:100", "*-1:0"]
+/// Optional configuration file, `$XDG_CONFIG_HOME/monb/config.toml` unless `--config` says otherwise.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub perceptual: bool,
+
+    // keyed by monitor identity, see `BackendDevice::id`
+    pub calibration: HashMap<String, Calibration>,
+
+    // same keys as `calibration`, `*` applies to every monitor
+    pub limits: HashMap<String, Limits>,
+
+    // sorted, so the first alias naming a monitor doesn't change between runs
+    pub aliases: BTreeMap<String, Alias>,
+
+    // brightness arguments applied with `monb scene <name>`, spans point to the definition for error messages
+    scenes: BTreeMap<String, Spanned<Vec<String>>>,
+
+    // file the configuration was loaded from, or would be loaded from if it existed
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+
+    #[serde(skip)]
+    scene_lines: HashMap<String, usize>
+}
+
+/// Scene from the configuration file, arguments aren't validated until the scene is applied.
+pub struct Scene<'a> {
+    pub name: &'a str,
+    pub arguments: &'a Vec<String>,
+    pub line: usize
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AliasTargets {
+    One(String),
+    Many(Vec<String>)
+}
+
+/// Scope a friendly name stands for, targets are scopes (`DP-2`, `@ABC123`, `1-2`, `internal`), not other aliases.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "AliasTargets")]
+pub struct Alias(pub BScope);
+
+impl TryFrom<AliasTargets> for Alias {
+    type Error = String;
+
+    fn try_from(value: AliasTargets) -> Result<Self, Self::Error> {
+        let targets: Vec<String> = match value {
+            AliasTargets::One(target) => vec![target],
+            AliasTargets::Many(targets) => targets
+        };
+
+        let mut scopes: Vec<BScope> = targets.iter()
+            .map(|target| BScope::from_str(target))
+            .collect::<Result<Vec<BScope>, String>>()?;
+
+        match scopes.len() {
+            0 => Err("Alias has no targets".into()),
+            1 => Ok(Alias(scopes.remove(0))),
+            _ => Ok(Alias(BScope::List(scopes)))
+        }
+    }
+}
+
+// aliases are used as scopes, so they have to look like identifiers and can't shadow named scopes
+fn validate_alias_name(name: &str) -> Result<(), String> {
+    match BScope::from_str(name) {
+        Ok(BScope::Id(id)) if !id.contains(|c: char| c == ':' || c == ',' || c.is_whitespace()) => Ok(()),
+        _ => Err(format!("`{}` can't be used as an alias name, it's not an identifier or it's a reserved scope", name))
+    }
+}
+
+// 1-based line of a byte offset
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|directory| directory.join(CONFIG_DIRECTORY).join(CONFIG_FILE))
+    }
+
+    /// Loads the configuration from `path`, or from the default path if `None`.
+    /// Only a missing default file is allowed, an explicitly provided file has to exist.
+    pub fn load(path: Option<&String>) -> Result<Self, String> {
+        let (path, explicit) = match path {
+            Some(p) => (PathBuf::from(p), true),
+            None => match Self::default_path() {
+                Some(p) => (p, false),
+                None => {
+                    debug!("No configuration directory, using default configuration");
+                    return Ok(Self::default());
+                }
+            }
+        };
+
+        let content: String = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(err) if err.kind() == ErrorKind::NotFound && !explicit => {
+                debug!("Configuration file `{}` not found, using default configuration", path.display());
+                return Ok(Self { path: Some(path), ..Self::default() });
+            },
+            Err(err) => return Err(format!("Failed to read configuration file `{}` ({})", path.display(), err))
+        };
+
+        debug!("Loading configuration from `{}`", path.display());
+        let mut config: Config = toml::from_str(&content).map_err(|err| match err.span() {
+            Some(span) => format!("Invalid configuration file `{}` at line {} ({})", path.display(), line_of(&content, span.start), err.message()),
+            None => format!("Invalid configuration file `{}` ({})", path.display(), err.message())
+        })?;
+
+        for (id, calibration) in &config.calibration {
+            calibration.validate().map_err(|err| format!("Invalid calibration for `{}` in `{}` ({})", id, path.display(), err))?;
+        }
+
+        for (id, limits) in &config.limits {
+            limits.validate().map_err(|err| format!("Invalid limits for `{}` in `{}` ({})", id, path.display(), err))?;
+        }
+
+        for name in config.aliases.keys() {
+            validate_alias_name(name).map_err(|err| format!("Invalid alias in `{}` ({})", path.display(), err))?;
+        }
+
+        config.scene_lines = config.scenes.iter()
+            .map(|(name, scene)| (name.clone(), line_of(&content, scene.span().start)))
+            .collect();
+        config.path = Some(path);
+
+        return Ok(config);
+    }
+
+    pub fn calibration(&self, id: &str) -> Option<&Calibration> {
+        self.calibration.get(id)
+    }
+
+    pub fn limits(&self, id: &str) -> Option<&Limits> {
+        self.limits.get(id)
+    }
+
+    /// Scenes are case-insensitive, like aliases.
+    pub fn scene(&self, name: &str) -> Option<Scene<'_>> {
+        self.scenes.iter()
+            .find(|(scene, _)| scene.eq_ignore_ascii_case(name))
+            .map(|(scene, arguments)| Scene {
+                name: scene,
+                arguments: arguments.get_ref(),
+                line: self.scene_lines.get(scene).copied().unwrap_or(0)
+            })
+    }
+
+    pub fn scene_names(&self) -> Vec<&String> {
+        self.scenes.keys().collect()
+    }
+
+    /// Aliases are case-insensitive, like monitor identifiers.
+    pub fn alias(&self, name: &str) -> Option<&Alias> {
+        self.aliases.iter().find(|(alias, _)| alias.eq_ignore_ascii_case(name)).map(|(_, alias)| alias)
+    }
+}
@@ -0,0 +1,177 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+use log::debug;
+
+pub mod packet;
+pub mod transport;
+
+use packet::{decode_get_vcp_reply, encode_get_vcp_request, encode_set_vcp_request, VcpValue, GET_VCP_REPLY_LENGTH};
+use transport::DdcTransport;
+
+// minimal delays required by the DDC/CI specification
+const GET_VCP_REPLY_DELAY: Duration = Duration::from_millis(40);
+const SET_VCP_DELAY: Duration = Duration::from_millis(50);
+const RETRY_DELAY: Duration = Duration::from_millis(100);
+const GET_VCP_RETRIES: usize = 3;
+
+/// DDC/CI session with a single display.
+#[derive(Debug)]
+pub struct Ddc<T: DdcTransport> {
+    transport: T,
+    delays: bool
+}
+
+impl<T: DdcTransport> Ddc<T> {
+    pub fn new(transport: T) -> Self {
+        Self { transport, delays: true }
+    }
+
+    /// Skips the protocol delays, for transports that don't talk to a real display.
+    pub fn without_delays(mut self) -> Self {
+        self.delays = false;
+        self
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    fn wait(&self, duration: Duration) {
+        if self.delays {
+            sleep(duration);
+        }
+    }
+
+    pub fn get_vcp(&mut self, code: u8) -> Result<VcpValue, String> {
+        let request: Vec<u8> = encode_get_vcp_request(code);
+        let mut last_error: String = String::new();
+
+        // displays often answer with null messages or garbage when they are busy
+        for attempt in 1..=GET_VCP_RETRIES {
+            self.transport.write(&request)?;
+            self.wait(GET_VCP_REPLY_DELAY);
+
+            let mut reply: [u8; GET_VCP_REPLY_LENGTH] = [0; GET_VCP_REPLY_LENGTH];
+            self.transport.read(&mut reply)?;
+
+            match decode_get_vcp_reply(code, &reply) {
+                Ok(value) => return Ok(value),
+                Err(err) => {
+                    debug!("Get VCP `0x{:02X}` attempt {}/{} failed ({})", code, attempt, GET_VCP_RETRIES, err);
+                    last_error = err;
+                    self.wait(RETRY_DELAY);
+                }
+            }
+        }
+
+        return Err(last_error);
+    }
+
+    pub fn set_vcp(&mut self, code: u8, value: u16) -> Result<(), String> {
+        self.transport.write(&encode_set_vcp_request(code, value))?;
+        self.wait(SET_VCP_DELAY);
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packet::encode_null_reply;
+    use transport::LoopbackTransport;
+
+    const BRIGHTNESS: u8 = 0x10;
+    const CONTRAST: u8 = 0x12;
+    const INPUT_SOURCE: u8 = 0x60;
+
+    // display that is busy for the first `busy_replies` reads and answers with null messages
+    #[derive(Debug)]
+    struct BusyTransport {
+        display: LoopbackTransport,
+        busy_replies: usize,
+        writes: usize
+    }
+
+    impl DdcTransport for BusyTransport {
+        fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+            self.writes += 1;
+            self.display.write(bytes)
+        }
+
+        fn read(&mut self, buffer: &mut [u8]) -> Result<(), String> {
+            if self.busy_replies == 0 {
+                return self.display.read(buffer);
+            }
+
+            self.busy_replies -= 1;
+            let reply: Vec<u8> = encode_null_reply();
+            buffer.fill(0);
+            buffer[..reply.len()].copy_from_slice(&reply);
+            return Ok(());
+        }
+    }
+
+    fn display() -> LoopbackTransport {
+        LoopbackTransport::default().with_value(BRIGHTNESS, true, 40, 100)
+    }
+
+    fn busy_display(busy_replies: usize) -> Ddc<BusyTransport> {
+        Ddc::new(BusyTransport { display: display(), busy_replies, writes: 0 }).without_delays()
+    }
+
+    #[test]
+    fn get_reads_the_display_value() {
+        let mut ddc: Ddc<LoopbackTransport> = Ddc::new(display()).without_delays();
+        assert_eq!(ddc.get_vcp(BRIGHTNESS), Ok(VcpValue { code: BRIGHTNESS, continuous: true, current: 40, max: 100 }));
+    }
+
+    #[test]
+    fn codes_are_read_as_non_continuous() {
+        let mut ddc: Ddc<LoopbackTransport> = Ddc::new(LoopbackTransport::default().with_value(INPUT_SOURCE, false, 0x0F, 0x11)).without_delays();
+        assert_eq!(ddc.get_vcp(INPUT_SOURCE), Ok(VcpValue { code: INPUT_SOURCE, continuous: false, current: 0x0F, max: 0x11 }));
+    }
+
+    #[test]
+    fn set_changes_the_display_value() {
+        let mut ddc: Ddc<LoopbackTransport> = Ddc::new(display()).without_delays();
+        ddc.set_vcp(BRIGHTNESS, 75).unwrap();
+
+        assert_eq!(ddc.transport().value(BRIGHTNESS).map(|value| value.current), Some(75));
+        assert_eq!(ddc.get_vcp(BRIGHTNESS).map(|value| value.current), Ok(75));
+    }
+
+    #[test]
+    fn set_above_max_is_clamped_by_the_display() {
+        let mut ddc: Ddc<LoopbackTransport> = Ddc::new(display()).without_delays();
+        ddc.set_vcp(BRIGHTNESS, 500).unwrap();
+
+        assert_eq!(ddc.get_vcp(BRIGHTNESS).map(|value| value.current), Ok(100));
+    }
+
+    #[test]
+    fn unsupported_code_fails_after_all_retries() {
+        let mut ddc: Ddc<LoopbackTransport> = Ddc::new(display()).without_delays();
+        assert_eq!(ddc.get_vcp(CONTRAST), Err("VCP code `0x12` is not supported by the display".into()));
+
+        // unknown codes are ignored by displays
+        ddc.set_vcp(CONTRAST, 50).unwrap();
+        assert_eq!(ddc.transport().value(CONTRAST), None);
+    }
+
+    #[test]
+    fn busy_display_is_retried() {
+        let mut ddc: Ddc<BusyTransport> = busy_display(GET_VCP_RETRIES - 1);
+
+        assert_eq!(ddc.get_vcp(BRIGHTNESS).map(|value| value.current), Ok(40));
+        assert_eq!(ddc.transport().writes, GET_VCP_RETRIES);
+    }
+
+    #[test]
+    fn retries_give_up_with_the_last_error() {
+        let mut ddc: Ddc<BusyTransport> = busy_display(GET_VCP_RETRIES);
+
+        assert_eq!(ddc.get_vcp(BRIGHTNESS), Err("Display replied with a DDC/CI null message".into()));
+        assert_eq!(ddc.transport().writes, GET_VCP_RETRIES);
+    }
+}
@@ -0,0 +1,276 @@
+// DDC/CI packet codec (VESA DDC/CI 1.1, MCCS 2.2)
+//
+// Host to display (written to I2C address 0x37):
+//     +--------+------------------+-----------------+-----------+
+//     | source | 0x80 | length    | payload...      | checksum  |
+//     +--------+------------------+-----------------+-----------+
+// The source byte is the host address (0x51), the checksum is XOR of the destination address (0x6E) and all other bytes.
+//
+// Display to host (read from I2C address 0x37):
+//     +--------+------------------+-----------------+-----------+
+//     | 0x6E   | 0x80 | length    | payload...      | checksum  |
+//     +--------+------------------+-----------------+-----------+
+// The checksum is XOR of the virtual host address (0x50) and all other bytes.
+
+pub const DDC_I2C_ADDRESS: u16 = 0x37;
+
+const DISPLAY_ADDRESS: u8 = 0x6E;
+const HOST_ADDRESS: u8 = 0x51;
+const VIRTUAL_HOST_ADDRESS: u8 = 0x50;
+const LENGTH_FLAG: u8 = 0x80;
+
+const GET_VCP_REQUEST: u8 = 0x01;
+const GET_VCP_REPLY: u8 = 0x02;
+const SET_VCP_REQUEST: u8 = 0x03;
+
+/// Length of a `Get VCP Feature` reply, including the address and checksum bytes.
+pub const GET_VCP_REPLY_LENGTH: usize = 11;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VcpValue {
+    pub code: u8,
+    pub continuous: bool,
+    pub current: u16,
+    pub max: u16
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcpRequest {
+    Get(u8),
+    Set(u8, u16)
+}
+
+fn checksum(initial: u8, bytes: &[u8]) -> u8 {
+    bytes.iter().fold(initial, |acc, byte| acc ^ byte)
+}
+
+fn encode(payload: &[u8]) -> Vec<u8> {
+    let mut packet: Vec<u8> = Vec::with_capacity(payload.len() + 3);
+    packet.push(HOST_ADDRESS);
+    packet.push(LENGTH_FLAG | payload.len() as u8);
+    packet.extend_from_slice(payload);
+    packet.push(checksum(DISPLAY_ADDRESS, &packet));
+
+    return packet;
+}
+
+pub fn encode_get_vcp_request(code: u8) -> Vec<u8> {
+    encode(&[GET_VCP_REQUEST, code])
+}
+
+pub fn encode_set_vcp_request(code: u8, value: u16) -> Vec<u8> {
+    let [high, low] = value.to_be_bytes();
+    encode(&[SET_VCP_REQUEST, code, high, low])
+}
+
+/// Decodes a `Get VCP Feature` reply for `code`. `bytes` is the whole buffer read from the display.
+pub fn decode_get_vcp_reply(code: u8, bytes: &[u8]) -> Result<VcpValue, String> {
+    if bytes.len() < 3 {
+        return Err(format!("DDC/CI reply is too short ({} bytes)", bytes.len()));
+    }
+
+    if bytes[0] != DISPLAY_ADDRESS {
+        return Err(format!("DDC/CI reply has invalid source address `0x{:02X}`", bytes[0]));
+    }
+
+    if bytes[1] & LENGTH_FLAG == 0 {
+        return Err(format!("DDC/CI reply has invalid length byte `0x{:02X}`", bytes[1]));
+    }
+
+    let length: usize = (bytes[1] & !LENGTH_FLAG) as usize;
+    if length == 0 {
+        // null message, the display is busy or doesn't know how to reply
+        return Err("Display replied with a DDC/CI null message".into());
+    }
+
+    if bytes.len() < length + 3 {
+        return Err(format!("DDC/CI reply is truncated, expected {} bytes but got {}", length + 3, bytes.len()));
+    }
+
+    let expected_checksum: u8 = checksum(VIRTUAL_HOST_ADDRESS, &bytes[..length + 2]);
+    if bytes[length + 2] != expected_checksum {
+        return Err(format!("DDC/CI reply has invalid checksum `0x{:02X}`, expected `0x{:02X}`", bytes[length + 2], expected_checksum));
+    }
+
+    let payload: &[u8] = &bytes[2..length + 2];
+    if payload.len() != 8 || payload[0] != GET_VCP_REPLY {
+        return Err(format!("DDC/CI reply is not a `Get VCP Feature` reply (opcode `0x{:02X}`)", payload[0]));
+    }
+
+    match payload[1] {
+        0x00 => {},
+        0x01 => return Err(format!("VCP code `0x{:02X}` is not supported by the display", code)),
+        result => return Err(format!("Display returned invalid result code `0x{:02X}`", result))
+    };
+
+    if payload[2] != code {
+        return Err(format!("DDC/CI reply is for VCP code `0x{:02X}` instead of `0x{:02X}`", payload[2], code));
+    }
+
+    return Ok(VcpValue {
+        code,
+        continuous: payload[3] == 0x00,
+        max: u16::from_be_bytes([payload[4], payload[5]]),
+        current: u16::from_be_bytes([payload[6], payload[7]])
+    });
+}
+
+/// Decodes a request written by the host, the display side of [`encode_get_vcp_request`] and [`encode_set_vcp_request`].
+pub fn decode_request(bytes: &[u8]) -> Result<VcpRequest, String> {
+    if bytes.len() < 3 || bytes[0] != HOST_ADDRESS || bytes[1] & LENGTH_FLAG == 0 {
+        return Err("DDC/CI request has invalid header".into());
+    }
+
+    let length: usize = (bytes[1] & !LENGTH_FLAG) as usize;
+    if bytes.len() != length + 3 {
+        return Err(format!("DDC/CI request has invalid length, expected {} bytes but got {}", length + 3, bytes.len()));
+    }
+
+    if bytes[length + 2] != checksum(DISPLAY_ADDRESS, &bytes[..length + 2]) {
+        return Err("DDC/CI request has invalid checksum".into());
+    }
+
+    match &bytes[2..length + 2] {
+        [GET_VCP_REQUEST, code] => Ok(VcpRequest::Get(*code)),
+        [SET_VCP_REQUEST, code, high, low] => Ok(VcpRequest::Set(*code, u16::from_be_bytes([*high, *low]))),
+        _ => Err(format!("Unsupported DDC/CI request opcode `0x{:02X}`", bytes[2]))
+    }
+}
+
+/// Encodes a `Get VCP Feature` reply, `None` replies with the "unsupported VCP code" result.
+pub fn encode_get_vcp_reply(code: u8, value: Option<VcpValue>) -> Vec<u8> {
+    let (result, continuous, max, current) = match value {
+        Some(v) => (0x00, v.continuous, v.max, v.current),
+        None => (0x01, true, 0, 0)
+    };
+
+    let [max_high, max_low] = max.to_be_bytes();
+    let [current_high, current_low] = current.to_be_bytes();
+
+    let mut packet: Vec<u8> = vec![
+        DISPLAY_ADDRESS, LENGTH_FLAG | 8,
+        GET_VCP_REPLY, result, code, if continuous { 0x00 } else { 0x01 },
+        max_high, max_low, current_high, current_low
+    ];
+    packet.push(checksum(VIRTUAL_HOST_ADDRESS, &packet));
+
+    return packet;
+}
+
+/// Reply sent by displays that have nothing to say.
+pub fn encode_null_reply() -> Vec<u8> {
+    let mut packet: Vec<u8> = vec![DISPLAY_ADDRESS, LENGTH_FLAG];
+    packet.push(checksum(VIRTUAL_HOST_ADDRESS, &packet));
+
+    return packet;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bytes as they appear on the wire (e.g. in `ddcutil --trace i2c`), the destination address 0x6E is not sent
+    // but is part of the request checksum. Checksums were computed by hand.
+
+    // Get VCP `0x10` (brightness)
+    const GET_BRIGHTNESS_REQUEST: [u8; 5] = [0x51, 0x82, 0x01, 0x10, 0xAC];
+
+    // Set VCP `0x10` (brightness) to 50
+    const SET_BRIGHTNESS_REQUEST: [u8; 7] = [0x51, 0x84, 0x03, 0x10, 0x00, 0x32, 0x9A];
+
+    // brightness 50 of 100, continuous
+    const BRIGHTNESS_REPLY: [u8; 11] = [0x6E, 0x88, 0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32, 0xF2];
+
+    // input source `0x0F` (DisplayPort 1) of `0x11`, non-continuous
+    const INPUT_REPLY: [u8; 11] = [0x6E, 0x88, 0x02, 0x00, 0x60, 0x01, 0x00, 0x11, 0x00, 0x0F, 0xCB];
+
+    // "unsupported VCP code" result for `0xDC`
+    const UNSUPPORTED_REPLY: [u8; 11] = [0x6E, 0x88, 0x02, 0x01, 0xDC, 0x00, 0x00, 0x00, 0x00, 0x00, 0x69];
+
+    const NULL_REPLY: [u8; 3] = [0x6E, 0x80, 0xBE];
+
+    #[test]
+    fn requests_are_encoded_like_recorded_packets() {
+        assert_eq!(encode_get_vcp_request(0x10), GET_BRIGHTNESS_REQUEST);
+        assert_eq!(encode_set_vcp_request(0x10, 50), SET_BRIGHTNESS_REQUEST);
+    }
+
+    #[test]
+    fn recorded_requests_are_decoded() {
+        assert_eq!(decode_request(&GET_BRIGHTNESS_REQUEST), Ok(VcpRequest::Get(0x10)));
+        assert_eq!(decode_request(&SET_BRIGHTNESS_REQUEST), Ok(VcpRequest::Set(0x10, 50)));
+    }
+
+    #[test]
+    fn requests_with_bad_checksum_or_opcode_are_rejected() {
+        let mut bad_checksum: [u8; 5] = GET_BRIGHTNESS_REQUEST;
+        bad_checksum[4] ^= 0xFF;
+        assert_eq!(decode_request(&bad_checksum), Err("DDC/CI request has invalid checksum".into()));
+
+        // `Capabilities Request` (0xF3), valid checksum
+        assert_eq!(decode_request(&[0x51, 0x83, 0xF3, 0x00, 0x00, 0x4F]), Err("Unsupported DDC/CI request opcode `0xF3`".into()));
+        assert_eq!(decode_request(&GET_BRIGHTNESS_REQUEST[..4]), Err("DDC/CI request has invalid length, expected 5 bytes but got 4".into()));
+    }
+
+    #[test]
+    fn recorded_replies_are_decoded() {
+        assert_eq!(decode_get_vcp_reply(0x10, &BRIGHTNESS_REPLY), Ok(VcpValue { code: 0x10, continuous: true, current: 50, max: 100 }));
+        assert_eq!(decode_get_vcp_reply(0x60, &INPUT_REPLY), Ok(VcpValue { code: 0x60, continuous: false, current: 0x0F, max: 0x11 }));
+    }
+
+    #[test]
+    fn replies_are_encoded_like_recorded_packets() {
+        assert_eq!(encode_get_vcp_reply(0x10, Some(VcpValue { code: 0x10, continuous: true, current: 50, max: 100 })), BRIGHTNESS_REPLY);
+        assert_eq!(encode_get_vcp_reply(0xDC, None), UNSUPPORTED_REPLY);
+        assert_eq!(encode_null_reply(), NULL_REPLY);
+    }
+
+    #[test]
+    fn reply_with_bad_checksum_is_rejected() {
+        let mut reply: [u8; 11] = BRIGHTNESS_REPLY;
+        reply[10] = 0xF3;
+        assert_eq!(decode_get_vcp_reply(0x10, &reply), Err("DDC/CI reply has invalid checksum `0xF3`, expected `0xF2`".into()));
+
+        // a flipped bit in the value is caught too
+        let mut reply: [u8; 11] = BRIGHTNESS_REPLY;
+        reply[9] = 0x33;
+        assert_eq!(decode_get_vcp_reply(0x10, &reply), Err("DDC/CI reply has invalid checksum `0xF2`, expected `0xF3`".into()));
+    }
+
+    #[test]
+    fn reply_with_wrong_opcode_is_rejected() {
+        // `Set VCP` opcode with a matching checksum
+        let reply: [u8; 11] = [0x6E, 0x88, 0x03, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32, 0xF3];
+        assert_eq!(decode_get_vcp_reply(0x10, &reply), Err("DDC/CI reply is not a `Get VCP Feature` reply (opcode `0x03`)".into()));
+    }
+
+    #[test]
+    fn reply_for_another_code_is_rejected() {
+        assert_eq!(decode_get_vcp_reply(0x12, &BRIGHTNESS_REPLY), Err("DDC/CI reply is for VCP code `0x10` instead of `0x12`".into()));
+    }
+
+    #[test]
+    fn truncated_replies_are_rejected() {
+        assert_eq!(decode_get_vcp_reply(0x10, &BRIGHTNESS_REPLY[..6]), Err("DDC/CI reply is truncated, expected 11 bytes but got 6".into()));
+        assert_eq!(decode_get_vcp_reply(0x10, &BRIGHTNESS_REPLY[..2]), Err("DDC/CI reply is too short (2 bytes)".into()));
+    }
+
+    #[test]
+    fn unsupported_vcp_code_is_reported() {
+        assert_eq!(decode_get_vcp_reply(0xDC, &UNSUPPORTED_REPLY), Err("VCP code `0xDC` is not supported by the display".into()));
+    }
+
+    #[test]
+    fn null_reply_is_reported() {
+        let mut buffer: [u8; GET_VCP_REPLY_LENGTH] = [0; GET_VCP_REPLY_LENGTH];
+        buffer[..3].copy_from_slice(&NULL_REPLY);
+        assert_eq!(decode_get_vcp_reply(0x10, &buffer), Err("Display replied with a DDC/CI null message".into()));
+    }
+
+    #[test]
+    fn reply_from_wrong_address_is_rejected() {
+        let mut reply: [u8; 11] = BRIGHTNESS_REPLY;
+        reply[0] = 0x6F;
+        assert_eq!(decode_get_vcp_reply(0x10, &reply), Err("DDC/CI reply has invalid source address `0x6F`".into()));
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use crate::ddc::packet::{decode_request, encode_get_vcp_reply, encode_null_reply, VcpRequest, VcpValue};
+
+/// Raw byte transport to the display's DDC/CI slave (I2C address 0x37).
+pub trait DdcTransport: Debug + Send {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), String>;
+    fn read(&mut self, buffer: &mut [u8]) -> Result<(), String>;
+}
+
+/// In-memory transport emulating a display, answers requests from its own VCP table.
+#[derive(Debug, Default)]
+pub struct LoopbackTransport {
+    values: HashMap<u8, VcpValue>,
+    reply: Option<Vec<u8>>
+}
+
+impl LoopbackTransport {
+    /// Adds a VCP code, `continuous` ones are ranges (brightness), the others are codes (input sources).
+    pub fn with_value(mut self, code: u8, continuous: bool, current: u16, max: u16) -> Self {
+        self.values.insert(code, VcpValue { code, continuous, current, max });
+        self
+    }
+
+    pub fn value(&self, code: u8) -> Option<VcpValue> {
+        self.values.get(&code).copied()
+    }
+}
+
+impl DdcTransport for LoopbackTransport {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        match decode_request(bytes)? {
+            VcpRequest::Get(code) => self.reply = Some(encode_get_vcp_reply(code, self.value(code))),
+            VcpRequest::Set(code, value) => {
+                // like real displays, unknown codes are silently ignored
+                if let Some(v) = self.values.get_mut(&code) {
+                    v.current = value.min(v.max);
+                }
+            }
+        };
+
+        return Ok(());
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<(), String> {
+        let reply: Vec<u8> = self.reply.take().unwrap_or_else(encode_null_reply);
+
+        // shorter replies are padded with zeroes, like a display that stopped sending
+        buffer.fill(0);
+        let length: usize = reply.len().min(buffer.len());
+        buffer[..length].copy_from_slice(&reply[..length]);
+        return Ok(());
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use self::linux::I2cDevTransport;
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::fs::{File, OpenOptions};
+    use std::io::{Read, Write};
+    use std::os::fd::AsRawFd;
+    use std::path::{Path, PathBuf};
+
+    use crate::ddc::packet::DDC_I2C_ADDRESS;
+    use super::DdcTransport;
+
+    // from linux/i2c-dev.h
+    const I2C_SLAVE: libc::c_ulong = 0x0703;
+
+    /// Transport talking to `/dev/i2c-*` through the i2c-dev kernel module.
+    #[derive(Debug)]
+    pub struct I2cDevTransport {
+        path: PathBuf,
+        file: File
+    }
+
+    impl I2cDevTransport {
+        pub fn open(path: &Path) -> Result<Self, String> {
+            let file: File = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)
+                .map_err(|err| format!("Failed to open `{}` ({})", path.display(), err))?;
+
+            // SAFETY: `file` is a valid open descriptor and I2C_SLAVE only takes an integer argument
+            let result = unsafe { libc::ioctl(file.as_raw_fd(), I2C_SLAVE, DDC_I2C_ADDRESS as libc::c_ulong) };
+            if result < 0 {
+                return Err(format!("Failed to select DDC/CI address on `{}` ({})", path.display(), std::io::Error::last_os_error()));
+            }
+
+            return Ok(Self { path: path.to_path_buf(), file });
+        }
+    }
+
+    impl DdcTransport for I2cDevTransport {
+        fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+            self.file.write_all(bytes).map_err(|err| format!("Failed to write to `{}` ({})", self.path.display(), err))
+        }
+
+        fn read(&mut self, buffer: &mut [u8]) -> Result<(), String> {
+            self.file.read_exact(buffer).map_err(|err| format!("Failed to read from `{}` ({})", self.path.display(), err))
+        }
+    }
+}
@@ -0,0 +1,244 @@
+const EDID_HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+const EDID_BLOCK_LENGTH: usize = 128;
+
+const MANUFACTURER_OFFSET: usize = 8;
+const PRODUCT_CODE_OFFSET: usize = 10;
+const SERIAL_NUMBER_OFFSET: usize = 12;
+const WEEK_OFFSET: usize = 16;
+const YEAR_OFFSET: usize = 17;
+const VERSION_OFFSET: usize = 18;
+const REVISION_OFFSET: usize = 19;
+const WIDTH_OFFSET: usize = 21;
+const HEIGHT_OFFSET: usize = 22;
+const DESCRIPTORS_OFFSET: usize = 54;
+const EXTENSIONS_OFFSET: usize = 126;
+
+const DESCRIPTOR_LENGTH: usize = 18;
+const DESCRIPTOR_COUNT: usize = 4;
+const DESCRIPTOR_TEXT_OFFSET: usize = 5;
+
+const DESCRIPTOR_SERIAL: u8 = 0xFF;
+const DESCRIPTOR_MODEL_NAME: u8 = 0xFC;
+
+// years are stored as an offset from 1990
+const YEAR_BASE: u16 = 1990;
+
+// week `0xFF` means the year is the model year instead of the manufacture year
+const MODEL_YEAR_WEEK: u8 = 0xFF;
+
+/// Decoded base EDID block of a display.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Edid {
+    // PNP ID, `DEL`, `AUO`, ...
+    pub manufacturer: String,
+    pub product_code: u16,
+
+    // numeric serial from the header, `0` if the manufacturer didn't set it
+    pub serial_number: u32,
+
+    // serial string from the display descriptors, preferred over the numeric one
+    pub serial_text: Option<String>,
+    pub model_name: Option<String>,
+
+    // physical size in centimeters, `None` for projectors and displays that don't report it
+    pub size: Option<(u8, u8)>,
+
+    pub year: u16,
+    pub model_year: bool,
+    pub version: (u8, u8)
+}
+
+impl Edid {
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.is_empty() {
+            return Err("EDID is empty".into());
+        }
+
+        if bytes.len() < EDID_BLOCK_LENGTH {
+            return Err(format!("EDID is truncated, expected at least {} bytes, got {}", EDID_BLOCK_LENGTH, bytes.len()));
+        }
+
+        let block: &[u8] = &bytes[..EDID_BLOCK_LENGTH];
+        if block[..EDID_HEADER.len()] != EDID_HEADER {
+            return Err("EDID header is missing, the data is corrupt or isn't an EDID".into());
+        }
+
+        let checksum: u8 = block.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        if checksum != 0 {
+            return Err(format!("EDID checksum mismatch (sum is `0x{:02X}` instead of `0x00`), the data is corrupt", checksum));
+        }
+
+        let extensions: usize = block[EXTENSIONS_OFFSET] as usize;
+        let expected_length: usize = EDID_BLOCK_LENGTH * (1 + extensions);
+        if bytes.len() < expected_length {
+            return Err(format!("EDID is truncated, {} extension block/s need {} bytes, got {}", extensions, expected_length, bytes.len()));
+        }
+
+        let week: u8 = block[WEEK_OFFSET];
+        let width: u8 = block[WIDTH_OFFSET];
+        let height: u8 = block[HEIGHT_OFFSET];
+
+        return Ok(Self {
+            manufacturer: decode_manufacturer(u16::from_be_bytes([block[MANUFACTURER_OFFSET], block[MANUFACTURER_OFFSET + 1]]))?,
+            product_code: u16::from_le_bytes([block[PRODUCT_CODE_OFFSET], block[PRODUCT_CODE_OFFSET + 1]]),
+            serial_number: u32::from_le_bytes([
+                block[SERIAL_NUMBER_OFFSET],
+                block[SERIAL_NUMBER_OFFSET + 1],
+                block[SERIAL_NUMBER_OFFSET + 2],
+                block[SERIAL_NUMBER_OFFSET + 3]
+            ]),
+            serial_text: descriptor_text(block, DESCRIPTOR_SERIAL),
+            model_name: descriptor_text(block, DESCRIPTOR_MODEL_NAME),
+            size: crate::ternary_operator!(width == 0 || height == 0, None, Some((width, height))),
+            year: YEAR_BASE + block[YEAR_OFFSET] as u16,
+            model_year: week == MODEL_YEAR_WEEK,
+            version: (block[VERSION_OFFSET], block[REVISION_OFFSET])
+        });
+    }
+
+    /// Serial used to identify the display, `None` if the display doesn't report one.
+    pub fn serial(&self) -> Option<String> {
+        if let Some(text) = &self.serial_text {
+            return Some(text.clone());
+        }
+
+        // unset serials are commonly `0` or `0x01010101`
+        match self.serial_number {
+            0 | 0x01010101 => None,
+            number => Some(number.to_string())
+        }
+    }
+}
+
+// three letters packed into 5 bits each, `1` is `A`
+fn decode_manufacturer(packed: u16) -> Result<String, String> {
+    [10, 5, 0].iter()
+        .map(|shift| match ((packed >> shift) & 0x1F) as u8 {
+            letter @ 1..=26 => Ok((b'A' + letter - 1) as char),
+            _ => Err(format!("EDID manufacturer ID `0x{:04X}` is invalid, the data is corrupt", packed))
+        })
+        .collect()
+}
+
+// descriptor strings are ASCII, terminated by a line feed and padded with spaces
+fn descriptor_text(block: &[u8], tag: u8) -> Option<String> {
+    let descriptor: &[u8] = (0..DESCRIPTOR_COUNT)
+        .map(|index| &block[DESCRIPTORS_OFFSET + index * DESCRIPTOR_LENGTH..DESCRIPTORS_OFFSET + (index + 1) * DESCRIPTOR_LENGTH])
+        .find(|descriptor| descriptor[..3] == [0, 0, 0] && descriptor[3] == tag)?;
+
+    let text: String = descriptor[DESCRIPTOR_TEXT_OFFSET..].iter()
+        .take_while(|byte| **byte != b'\n')
+        .map(|byte| *byte as char)
+        .collect();
+
+    Some(text.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // base block with the fields of a Dell U2415 (extension blocks dropped), the other fixtures are broken copies of it
+    const U2415: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/edid/dell_u2415.bin"));
+    const U2415_TRUNCATED: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/edid/dell_u2415_truncated.bin"));
+    const U2415_BAD_HEADER: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/edid/dell_u2415_bad_header.bin"));
+    const U2415_BAD_CHECKSUM: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/edid/dell_u2415_bad_checksum.bin"));
+
+    // sets bytes and fixes the checksum, so only the changed fields are invalid
+    fn patched(changes: &[(usize, u8)]) -> Vec<u8> {
+        let mut bytes: Vec<u8> = U2415.to_vec();
+        for (offset, value) in changes {
+            bytes[*offset] = *value;
+        }
+        let sum: u8 = bytes[..EDID_BLOCK_LENGTH - 1].iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        bytes[EDID_BLOCK_LENGTH - 1] = 0u8.wrapping_sub(sum);
+        return bytes;
+    }
+
+    #[test]
+    fn dump_is_decoded() {
+        let edid: Edid = Edid::parse(U2415).unwrap();
+
+        assert_eq!(edid, Edid {
+            manufacturer: "DEL".into(),
+            product_code: 0xA0C4,
+            serial_number: 0x4A4B4C31,
+            serial_text: Some("7MT0174C1ABL".into()),
+            model_name: Some("DELL U2415".into()),
+            size: Some((53, 30)),
+            year: 2017,
+            model_year: false,
+            version: (1, 4)
+        });
+        assert_eq!(edid.serial(), Some("7MT0174C1ABL".into()));
+    }
+
+    #[test]
+    fn numeric_serial_is_used_without_a_serial_descriptor() {
+        // the serial descriptor turned into an unused one
+        let edid: Edid = Edid::parse(&patched(&[(DESCRIPTORS_OFFSET + DESCRIPTOR_LENGTH + 3, 0x10)])).unwrap();
+
+        assert_eq!(edid.serial_text, None);
+        assert_eq!(edid.model_name, Some("DELL U2415".into()));
+        assert_eq!(edid.serial(), Some(0x4A4B4C31u32.to_string()));
+    }
+
+    #[test]
+    fn unset_serials_are_none() {
+        let mut edid: Edid = Edid::parse(U2415).unwrap();
+        edid.serial_text = None;
+
+        edid.serial_number = 0;
+        assert_eq!(edid.serial(), None);
+        edid.serial_number = 0x01010101;
+        assert_eq!(edid.serial(), None);
+    }
+
+    #[test]
+    fn model_year_and_missing_size_are_decoded() {
+        let edid: Edid = Edid::parse(&patched(&[(WEEK_OFFSET, MODEL_YEAR_WEEK), (WIDTH_OFFSET, 0)])).unwrap();
+
+        assert!(edid.model_year);
+        assert_eq!(edid.year, 2017);
+        assert_eq!(edid.size, None);
+    }
+
+    #[test]
+    fn trailing_extension_blocks_are_ignored() {
+        let mut bytes: Vec<u8> = patched(&[(EXTENSIONS_OFFSET, 1)]);
+        bytes.extend_from_slice(&[0u8; EDID_BLOCK_LENGTH]);
+
+        assert_eq!(Edid::parse(&bytes).unwrap().model_name, Some("DELL U2415".into()));
+    }
+
+    #[test]
+    fn empty_dump_is_rejected() {
+        assert_eq!(Edid::parse(&[]), Err("EDID is empty".into()));
+    }
+
+    #[test]
+    fn truncated_dump_is_rejected() {
+        assert_eq!(Edid::parse(U2415_TRUNCATED), Err("EDID is truncated, expected at least 128 bytes, got 100".into()));
+    }
+
+    #[test]
+    fn missing_extension_blocks_are_rejected() {
+        assert_eq!(Edid::parse(&patched(&[(EXTENSIONS_OFFSET, 1)])), Err("EDID is truncated, 1 extension block/s need 256 bytes, got 128".into()));
+    }
+
+    #[test]
+    fn bad_header_is_rejected() {
+        assert_eq!(Edid::parse(U2415_BAD_HEADER), Err("EDID header is missing, the data is corrupt or isn't an EDID".into()));
+    }
+
+    #[test]
+    fn bad_checksum_is_rejected() {
+        assert_eq!(Edid::parse(U2415_BAD_CHECKSUM), Err("EDID checksum mismatch (sum is `0x01` instead of `0x00`), the data is corrupt".into()));
+    }
+
+    #[test]
+    fn invalid_manufacturer_is_rejected() {
+        // first letter `0`, which isn't a letter
+        assert_eq!(Edid::parse(&patched(&[(MANUFACTURER_OFFSET, 0x00)])), Err("EDID manufacturer ID `0x00AC` is invalid, the data is corrupt".into()));
+    }
+}
@@ -0,0 +1,251 @@
+use std::num::IntErrorKind;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use clap::ArgMatches;
+use log::debug;
+use strum::{Display, EnumIter, IntoStaticStr};
+
+use crate::backend::DeviceError;
+use crate::cli::params::{ARG_FADE_ID, ARG_EASING_ID};
+use crate::cli::regexes::FADE_DURATION_REGEX;
+use crate::signal;
+
+// time between two brightness changes, slow devices (DDC/CI) simply skip steps
+const FADE_STEP_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Default, Clone, Copy)]
+#[derive(EnumIter, IntoStaticStr, Display)]
+pub enum Easing {
+    #[default]
+    #[strum(serialize = "linear")]          Linear,
+
+    #[strum(serialize = "ease-in-out")]     EaseInOut,
+    #[strum(serialize = "exponential")]     Exponential
+}
+
+impl TryFrom<Option<&String>> for Easing {
+    type Error = String;
+
+    fn try_from(value: Option<&String>) -> Result<Self, Self::Error> {
+        let potential_easing: &String = match value {
+            None => return Ok(Self::default()),
+            Some(v) => v
+        };
+
+        debug!("Parsing easing: `{}`", potential_easing);
+
+        match potential_easing.trim().to_lowercase().as_str() {
+            "linear" => Ok(Self::Linear),
+            "ease-in-out" | "ease_in_out" | "ease" => Ok(Self::EaseInOut),
+            "exponential" | "exp" => Ok(Self::Exponential),
+            _ => Err(format!("Invalid easing: `{}`", potential_easing))
+        }
+    }
+}
+
+impl Easing {
+    // maps progress in time (0..=1) to progress in brightness (0..=1)
+    fn apply(&self, t: f64) -> f64 {
+        let t: f64 = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            },
+            Self::Exponential => (2f64.powf(10.0 * t) - 1.0) / 1023.0
+        }
+    }
+}
+
+/// Parses durations like `500ms`, `2s` or `1min`, seconds are the default unit.
+/// `name` is the capitalized name of the value used in errors, e.g. `Fade duration`.
+pub fn parse_duration(input: &String, name: &str) -> Result<Duration, String> {
+    debug!("Parsing {}: `{}`", name.to_lowercase(), input);
+
+    let captures = match FADE_DURATION_REGEX.captures(input.trim()) {
+        Some(c) => c,
+        None => return Err(format!("Input `{}` is not a valid {}", input, name.to_lowercase()))
+    };
+
+    let value: u64 = match captures[1].parse::<u64>() {
+        Ok(v) => v,
+        Err(err) => match err.kind() {
+            IntErrorKind::PosOverflow => return Err(format!("{} `{}` is too big", name, input)),
+            _ => return Err(format!("{} `{}` is not valid", name, input))
+        }
+    };
+
+    let duration: Option<Duration> = match captures.get(2).map(|unit| unit.as_str().to_lowercase()) {
+        Some(unit) if unit == "ms" => Some(Duration::from_millis(value)),
+        Some(unit) if unit == "m" || unit == "min" => value.checked_mul(60).map(Duration::from_secs),
+        _ => Some(Duration::from_secs(value)) // seconds are the default unit
+    };
+
+    duration.ok_or_else(|| format!("{} `{}` is too big", name, input))
+}
+
+/// Fade shared by all monitors of a single invocation.
+#[derive(Debug, Clone, Copy)]
+pub struct Fade {
+    pub easing: Easing,
+    pub deadline: Instant
+}
+
+#[derive(Debug, Default)]
+pub struct FadeInfo {
+    pub duration: Option<Duration>,
+    pub easing: Easing
+}
+
+impl TryFrom<&ArgMatches> for FadeInfo {
+    type Error = String;
+
+    fn try_from(value: &ArgMatches) -> Result<Self, Self::Error> {
+        let duration: Option<Duration> = match value.get_one::<String>(ARG_FADE_ID) {
+            None => None,
+            Some(d) => Some(parse_duration(d, "Fade duration")?)
+        };
+
+        Ok(FadeInfo {
+            duration: duration.filter(|d| !d.is_zero()),
+            easing: Easing::try_from(value.get_one::<String>(ARG_EASING_ID))?
+        })
+    }
+}
+
+impl FadeInfo {
+    /// Starts the fade clock, `None` if fading is disabled.
+    pub fn start(&self) -> Option<Fade> {
+        self.duration.map(|duration| Fade { easing: self.easing, deadline: Instant::now() + duration })
+    }
+}
+
+/// Steps from `from` to `to` until `deadline`. Every monitor shares the same deadline, so they all finish together.
+/// Returns the last value that was set, which differs from `to` only if the fade was interrupted.
+/// A failed step stops the fade, the error comes with the last value that was set before it.
+pub fn fade<F>(from: u32, to: u32, fade: &Fade, set: F) -> Result<u32, (u32, DeviceError)>
+where
+    F: Fn(u32) -> Result<(), DeviceError>
+{
+    let Fade { easing, deadline } = *fade;
+    let start: Instant = Instant::now();
+    let total: Duration = deadline.saturating_duration_since(start);
+    let mut reached: u32 = from;
+
+    loop {
+        if signal::is_interrupted() {
+            debug!("Fade interrupted at `{}`", reached);
+            return Ok(reached);
+        }
+
+        let now: Instant = Instant::now();
+        let t: f64 = if now >= deadline || total.is_zero() { 1.0 } else { (now - start).as_secs_f64() / total.as_secs_f64() };
+        let value: u32 = (from as f64 + (to as f64 - from as f64) * easing.apply(t)).round() as u32;
+
+        if value != reached {
+            set(value).map_err(|err| (reached, err))?;
+            reached = value;
+        }
+
+        if t >= 1.0 {
+            break;
+        }
+
+        sleep(FADE_STEP_INTERVAL.min(deadline.saturating_duration_since(Instant::now())));
+    }
+
+    // rounding could stop one step short of the target
+    if reached != to {
+        set(to).map_err(|err| (reached, err))?;
+        reached = to;
+    }
+
+    return Ok(reached);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    fn fade_over(duration: Duration) -> Fade {
+        Fade { easing: Easing::Linear, deadline: Instant::now() + duration }
+    }
+
+    #[test]
+    fn easings_start_at_0_and_end_at_1() {
+        for easing in Easing::iter() {
+            assert_eq!(easing.apply(0.0), 0.0, "{}", easing);
+            assert_eq!(easing.apply(1.0), 1.0, "{}", easing);
+        }
+    }
+
+    #[test]
+    fn easings_are_monotonic() {
+        for easing in Easing::iter() {
+            for step in 1..=1000 {
+                let (previous, t): (f64, f64) = ((step - 1) as f64 / 1000.0, step as f64 / 1000.0);
+                assert!(easing.apply(t) >= easing.apply(previous), "{} at {}", easing, t);
+            }
+        }
+    }
+
+    #[test]
+    fn easings_clamp_progress() {
+        for easing in Easing::iter() {
+            assert_eq!(easing.apply(-0.5), 0.0, "{}", easing);
+            assert_eq!(easing.apply(1.5), 1.0, "{}", easing);
+        }
+    }
+
+    #[test]
+    fn short_fades_take_one_step_per_interval() {
+        let duration: Duration = Duration::from_millis(100);
+        let values: RefCell<Vec<u32>> = RefCell::new(Vec::new());
+
+        let reached = fade(0, 100, &fade_over(duration), |value| { values.borrow_mut().push(value); Ok(()) });
+
+        let values: Vec<u32> = values.into_inner();
+        let intervals: usize = (duration.as_millis() / FADE_STEP_INTERVAL.as_millis()) as usize;
+        assert_eq!(reached.ok(), Some(100));
+        assert_eq!(values.last(), Some(&100));
+        assert!(!values.is_empty() && values.len() <= intervals + 2, "{:?}", values);
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", values);
+    }
+
+    #[test]
+    fn finished_fades_set_the_target_once() {
+        let values: RefCell<Vec<u32>> = RefCell::new(Vec::new());
+
+        let reached = fade(80, 20, &fade_over(Duration::ZERO), |value| { values.borrow_mut().push(value); Ok(()) });
+
+        assert_eq!(reached.ok(), Some(20));
+        assert_eq!(values.into_inner(), vec![20]);
+    }
+
+    #[test]
+    fn failed_steps_return_the_last_value_that_was_set() {
+        let values: RefCell<Vec<u32>> = RefCell::new(Vec::new());
+        let set = |value: u32| -> Result<(), DeviceError> {
+            if value > 50 {
+                return Err(DeviceError::Other("device disconnected".into()));
+            }
+            values.borrow_mut().push(value);
+            return Ok(());
+        };
+
+        let (reached, err) = fade(0, 100, &fade_over(Duration::from_millis(200)), set).unwrap_err();
+
+        assert_eq!(reached, values.borrow().last().copied().unwrap_or(0));
+        assert!(reached <= 50, "{}", reached);
+        assert_eq!(err.to_string(), "device disconnected");
+    }
+}
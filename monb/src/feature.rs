@@ -0,0 +1,69 @@
+use log::debug;
+use strum::{Display, EnumIter, IntoStaticStr};
+
+/// Monitor control that brightness arguments are applied to, selected with `--feature`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(EnumIter, IntoStaticStr, Display)]
+pub enum Feature {
+    #[default]
+    #[strum(serialize = "brightness")]  Brightness,
+
+    #[strum(serialize = "contrast")]    Contrast,
+    #[strum(serialize = "volume")]      Volume,
+    #[strum(serialize = "input")]       InputSource,
+    #[strum(serialize = "power")]       PowerMode
+}
+
+impl TryFrom<Option<&String>> for Feature {
+    type Error = String;
+
+    fn try_from(value: Option<&String>) -> Result<Self, Self::Error> {
+        let potential_feature: &String = match value {
+            None => return Ok(Self::default()),
+            Some(v) => v
+        };
+
+        debug!("Parsing feature: `{}`", potential_feature);
+
+        match potential_feature.trim().to_lowercase().as_str() {
+            "brightness" | "luminance" => Ok(Self::Brightness),
+            "contrast" => Ok(Self::Contrast),
+            "volume" | "speaker" => Ok(Self::Volume),
+            "input" | "input_source" => Ok(Self::InputSource),
+            "power" | "power_mode" => Ok(Self::PowerMode),
+            _ => Err(format!("Invalid feature: `{}`", potential_feature))
+        }
+    }
+}
+
+impl Feature {
+    /// MCCS VCP code of the feature.
+    pub fn vcp_code(&self) -> u8 {
+        match self {
+            Self::Brightness => 0x10,
+            Self::Contrast => 0x12,
+            Self::Volume => 0x62,
+            Self::InputSource => 0x60,
+            Self::PowerMode => 0xD6
+        }
+    }
+
+    /// Continuous features are scaled to percentages, the others (input source, power mode) are raw codes.
+    pub fn is_continuous(&self) -> bool {
+        match self {
+            Self::Brightness | Self::Contrast | Self::Volume => true,
+            Self::InputSource | Self::PowerMode => false
+        }
+    }
+
+    /// Name used in log messages, e.g. "failed to set monitor input source".
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Brightness => "brightness",
+            Self::Contrast => "contrast",
+            Self::Volume => "volume",
+            Self::InputSource => "input source",
+            Self::PowerMode => "power mode"
+        }
+    }
+}
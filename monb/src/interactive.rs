@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use crate::backend::BrightnessBackend;
+use crate::cli::params::{Arguments, BAction, BArg, BScope, BValue, ARG_OUTPUT_LONG_NAME, SUBCOMMAND_INTERACTIVE_NAME};
+use crate::signal;
+use crate::monitor::{apply_setter_barg, get_all_devices, read_values, Monitor};
+use crate::watch::Screen;
+
+// arrow keys step by `STEP`, with shift by `FINE_STEP`
+const STEP: usize = 5;
+const FINE_STEP: usize = 1;
+
+// values are read again after this long without a key press, so changes made elsewhere show up
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+const HELP_LINE: &'static str = "↑↓ select, ←→ ±5 (shift ±1), Home/End min/max, 0-9 exact value, `a` link all, `q` quit";
+
+struct Session<'a> {
+    arguments: &'a Arguments,
+    devices: &'a HashMap<usize, Monitor>,
+    indices: Vec<usize>,
+    readings: Vec<(usize, Result<u32, String>)>,
+
+    selected: usize,
+    linked: bool,
+
+    // digits typed so far, `Some` while an exact value is being entered
+    input: Option<String>,
+
+    // result of the last change, shown below the monitors
+    message: Option<String>
+}
+
+impl Session<'_> {
+    fn refresh(&mut self) {
+        self.readings = read_values(self.devices, &self.indices);
+    }
+
+    // every change goes through the same path as command line arguments, so limits, clamping and state stay consistent
+    fn apply(&mut self, action: BAction, brightness: impl Fn(&Monitor) -> usize) {
+        let targets: Vec<usize> = match self.linked {
+            true => self.indices.clone(),
+            false => vec![self.indices[self.selected]]
+        };
+
+        let mut errors: Vec<String> = Vec::<String>::new();
+        for index in targets {
+            let monitor: &Monitor = &self.devices[&index];
+            let barg: BArg = BArg::new(BScope::Index(index), BValue::new(action, brightness(monitor).min(self.arguments.value_max)));
+
+            if let Err(err) = apply_setter_barg(monitor, &barg, None) {
+                errors.push(err);
+            }
+        }
+
+        self.message = crate::ternary_operator!(errors.is_empty(), None, Some(errors.join(", ")));
+        self.refresh();
+    }
+
+    // `false` once the user quits
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let step: usize = crate::ternary_operator!(key.modifiers.contains(KeyModifiers::SHIFT), FINE_STEP, STEP);
+
+        if let Some(input) = &mut self.input {
+            match key.code {
+                KeyCode::Char(c) if c.is_ascii_digit() && input.len() < 10 => input.push(c),
+                KeyCode::Backspace => { input.pop(); },
+                KeyCode::Enter => {
+                    let value: Option<usize> = input.parse::<usize>().ok();
+                    self.input = None;
+                    if let Some(v) = value {
+                        self.apply(BAction::Set, |_| v);
+                    }
+                },
+                KeyCode::Esc => self.input = None,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+                _ => {}
+            };
+
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(self.indices.len() - 1),
+
+            KeyCode::Right | KeyCode::Char('l') => self.apply(BAction::Inc, |_| step),
+            KeyCode::Left | KeyCode::Char('h') => self.apply(BAction::Dec, |_| step),
+            KeyCode::Home => self.apply(BAction::Set, |_| 0),
+            KeyCode::End => self.apply(BAction::Set, |monitor| monitor.max() as usize),
+
+            KeyCode::Char('a') => self.linked = !self.linked,
+            KeyCode::Char(c) if c.is_ascii_digit() => self.input = Some(c.to_string()),
+            _ => {}
+        };
+
+        return true;
+    }
+
+    fn lines(&self) -> Vec<(String, bool)> {
+        let mut lines: Vec<(String, bool)> = vec![
+            (format!("monb {} - {}", SUBCOMMAND_INTERACTIVE_NAME, HELP_LINE), false),
+            (String::new(), false)
+        ];
+
+        for (position, (index, reading)) in self.readings.iter().enumerate() {
+            let monitor: &Monitor = &self.devices[index];
+            let line: String = match reading {
+                Ok(value) => monitor.line(*value, monitor.max(), &self.arguments.progressbar_info, self.arguments.template.as_ref()),
+                Err(err) => format!("{} - unavailable ({})", monitor.name, err)
+            };
+
+            let selected: bool = self.linked || position == self.selected;
+            lines.push((format!("{} {}", crate::ternary_operator!(selected, ">", " "), line), selected));
+        }
+
+        lines.push((String::new(), false));
+        lines.push((crate::ternary_operator!(self.linked, "Linked: changes apply to all monitors".into(), String::new()), false));
+        lines.push((match (&self.input, &self.message) {
+            (Some(input), _) => format!("Set to: {}_ (Enter to apply, Esc to cancel)", input),
+            (None, Some(message)) => message.clone(),
+            (None, None) => String::new()
+        }, false));
+
+        return lines;
+    }
+}
+
+/// Lists all monitors with their progressbars and changes their brightness with the keyboard.
+pub fn run_interactive(arguments: Arguments) -> Result<(), String> {
+    if arguments.output.is_structured() {
+        return Err(format!("`{}` can't be combined with `--{} {}`", SUBCOMMAND_INTERACTIVE_NAME, ARG_OUTPUT_LONG_NAME, arguments.output));
+    }
+    if !arguments.feature.is_continuous() {
+        return Err(format!("`{}` only works with continuous features, {} is a code", SUBCOMMAND_INTERACTIVE_NAME, arguments.feature.description()));
+    }
+
+    let backend: Box<dyn BrightnessBackend> = arguments.backend.create(arguments.flag_raw)?;
+    let devices: HashMap<usize, Monitor> = get_all_devices(backend.as_ref(), &arguments)?;
+
+    let mut indices: Vec<usize> = devices.keys().copied().collect();
+    indices.sort();
+
+    let mut session: Session = Session {
+        arguments: &arguments,
+        devices: &devices,
+        indices,
+        readings: vec![],
+        selected: 0,
+        linked: false,
+        input: None,
+        message: None
+    };
+    session.refresh();
+
+    // raw mode turns Ctrl-C into a key press, the handler only catches signals sent by other processes
+    signal::install_interrupt_handler();
+    let mut screen: Screen = Screen::enter(true)?;
+    screen.draw(&session.lines(), true).map_err(|err| format!("Failed to draw ({})", err))?;
+
+    while !signal::is_interrupted() {
+        let has_event: bool = event::poll(REFRESH_INTERVAL).map_err(|err| format!("Failed to read the terminal ({})", err))?;
+        let clear: bool = match has_event {
+            false => {
+                session.refresh();
+                false
+            },
+            true => match event::read().map_err(|err| format!("Failed to read the terminal ({})", err))? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    if !session.handle_key(key) {
+                        break;
+                    }
+                    false
+                },
+                Event::Resize(_, _) => true,
+                _ => continue
+            }
+        };
+
+        screen.draw(&session.lines(), clear).map_err(|err| format!("Failed to draw ({})", err))?;
+    }
+
+    return Ok(());
+}
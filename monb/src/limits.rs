@@ -0,0 +1,119 @@
+use serde::Deserialize;
+
+// Example limits, in brightness percentages:
+// [limits."*"]
+// max = 90
+//
+// [limits."DP-1"]
+// min = 5
+/// Floor and ceiling every brightness setter respects, `*` applies to all monitors.
+/// Monitor limits override the global ones field by field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Limits {
+    pub min: Option<u32>,
+    pub max: Option<u32>
+}
+
+impl Limits {
+    pub fn validate(&self) -> Result<(), String> {
+        let min: u32 = self.min.unwrap_or(0);
+        let max: u32 = self.max.unwrap_or(100);
+        if max > 100 {
+            return Err(format!("Limit max `{}` is bigger than 100", max));
+        }
+        if min > max {
+            return Err(format!("Limit min `{}` is bigger than max `{}`", min, max));
+        }
+
+        return Ok(());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.is_none() && self.max.is_none()
+    }
+
+    pub fn or(self, fallback: Limits) -> Limits {
+        Limits {
+            min: self.min.or(fallback.min),
+            max: self.max.or(fallback.max)
+        }
+    }
+
+    /// Limits on the `0..=max` scale, the floor rounds up and the ceiling rounds down so both stay inside the limits.
+    pub fn bounds(&self, max: u32) -> (u32, u32) {
+        let scale = |percentage: u32, round_up: bool| -> u32 {
+            let value: u64 = percentage as u64 * max as u64;
+            crate::ternary_operator!(round_up, value.div_ceil(100), value / 100) as u32
+        };
+
+        let floor: u32 = self.min.map_or(0, |min| scale(min, true));
+        let ceiling: u32 = self.max.map_or(max, |limit| scale(limit, false));
+        return (floor, ceiling.max(floor));
+    }
+}
+
+impl ToString for Limits {
+    fn to_string(&self) -> String {
+        format!("{}..={}", self.min.unwrap_or(0), self.max.unwrap_or(100))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(min: Option<u32>, max: Option<u32>) -> Limits {
+        Limits { min, max }
+    }
+
+    #[test]
+    fn bounds_are_the_whole_scale_without_limits() {
+        assert_eq!(Limits::default().bounds(100), (0, 100));
+        assert_eq!(Limits::default().bounds(19393), (0, 19393));
+    }
+
+    #[test]
+    fn bounds_stay_inside_the_limits_on_raw_scales() {
+        // 5% of 19393 is 969.65 and 90% is 17453.7
+        assert_eq!(limits(Some(5), Some(90)).bounds(19393), (970, 17453));
+        assert_eq!(limits(Some(5), Some(90)).bounds(100), (5, 90));
+    }
+
+    #[test]
+    fn floor_wins_when_rounding_crosses_the_bounds() {
+        // 50% of 7 is 3.5, the floor rounds up to 4 and the ceiling down to 3
+        assert_eq!(limits(Some(50), Some(50)).bounds(7), (4, 4));
+    }
+
+    #[test]
+    fn floor_wins_when_merged_limits_cross() {
+        // `[limits."*"] min = 60` with `[limits."DP-1"] max = 40`
+        let merged: Limits = limits(None, Some(40)).or(limits(Some(60), None));
+
+        assert!(merged.validate().is_err());
+        assert_eq!(merged.bounds(100), (60, 60));
+    }
+
+    #[test]
+    fn monitor_limits_override_global_ones_field_by_field() {
+        let global: Limits = limits(Some(10), Some(90));
+
+        assert_eq!(limits(None, Some(50)).or(global), limits(Some(10), Some(50)));
+        assert_eq!(limits(Some(20), None).or(global), limits(Some(20), Some(90)));
+        assert_eq!(limits(Some(0), Some(100)).or(global), limits(Some(0), Some(100)));
+        assert_eq!(Limits::default().or(global), global);
+    }
+
+    #[test]
+    fn min_bigger_than_max_is_rejected() {
+        assert_eq!(limits(Some(60), Some(40)).validate(), Err("Limit min `60` is bigger than max `40`".into()));
+        assert_eq!(limits(Some(101), None).validate(), Err("Limit min `101` is bigger than max `100`".into()));
+        assert_eq!(limits(Some(40), Some(40)).validate(), Ok(()));
+    }
+
+    #[test]
+    fn max_above_100_is_rejected() {
+        assert_eq!(limits(None, Some(150)).validate(), Err("Limit max `150` is bigger than 100".into()));
+    }
+}
@@ -1,34 +1,47 @@
-use clap::{ArgAction, Arg, ArgMatches};
+use clap::{ArgAction, Arg, ArgMatches, Command};
 
 mod parser;
 mod cli;
 mod monitor;
 mod progressbar;
+mod backend;
+mod ddc;
+mod feature;
+mod fade;
+mod signal;
+mod perceptual;
+mod config;
+mod calibration;
+mod limits;
+mod edid;
+mod state;
+mod scene;
+mod output;
+mod template;
+mod statusbar;
+mod watch;
+mod interactive;
 
 use cli::params::{self, Arguments};
 use log::{error, debug};
 use logger::Logger;
-use crate::monitor::apply_arguments;
+use crate::monitor::{apply_arguments, list_monitors, restore_state};
+use crate::scene::{apply_scene, save_scene};
+use crate::statusbar::{run_status_bar, StatusBarInfo};
+use crate::watch::{watch, WatchInfo};
+use crate::interactive::run_interactive;
 
-fn main() {
-
-    // quickfix: clap color errors
-    // clap can print error message in color
-    // before logger even starts
-    if let Some(_) = std::env::args_os().find(|arg| arg.to_ascii_lowercase() == "--nocolor") {
-        std::env::set_var("NO_COLOR", "1");
-        std::env::set_var("CLICOLOR", "0");
-    }
-
-    let cli_matches: ArgMatches = clap::command!()
-        .args_conflicts_with_subcommands(true)
+// global flags work in front of subcommands too (`monb -v list`), so a subcommand name is never taken as a brightness argument
+fn command() -> Command {
+    clap::command!()
+        .subcommand_precedence_over_arg(true)
         .args([
             Arg::new(params::ARG_BARGS_ID)
                 .allow_negative_numbers(true)
                 .value_name(params::ARG_BARGS_NAME)
                 .help(params::ARG_BARGS_HELP.as_str())
                 .action(ArgAction::Append),
-
+    
             // --------------------------------------------- FLAGS ---------------------------------------------
             Arg::new(params::FLAG_ZERO_ID)
                 .value_name(params::FLAG_ZERO_NAME)
@@ -37,14 +50,29 @@ fn main() {
                 .help(params::FLAG_ZERO_HELP)
                 .action(ArgAction::SetTrue)
                 .global(true),
-
+    
             Arg::new(params::FLAG_COLOR_ID)
                 .value_name(params::FLAG_COLOR_NAME)
                 .long(params::FLAG_COLOR_LONG_NAME)
                 .help(params::FLAG_COLOR_HELP)
                 .action(ArgAction::SetFalse)
                 .global(true),
-
+    
+            Arg::new(params::FLAG_RAW_ID)
+                .value_name(params::FLAG_RAW_NAME)
+                .long(params::FLAG_RAW_LONG_NAME)
+                .help(params::FLAG_RAW_HELP)
+                .action(ArgAction::SetTrue)
+                .global(true),
+    
+            Arg::new(params::FLAG_PERCEPTUAL_ID)
+                .value_name(params::FLAG_PERCEPTUAL_NAME)
+                .short(params::FLAG_PERCEPTUAL_SHORT_NAME)
+                .long(params::FLAG_PERCEPTUAL_LONG_NAME)
+                .help(params::FLAG_PERCEPTUAL_HELP)
+                .action(ArgAction::SetTrue)
+                .global(true),
+    
             Arg::new(params::FLAG_VERBOSE_ID)
                 .value_name(params::FLAG_VERBOSE_NAME)
                 .short(params::FLAG_VERBOSE_SHORT_NAME)
@@ -53,7 +81,7 @@ fn main() {
                 .action(ArgAction::SetTrue)
                 .global(true),
             // -------------------------------------------------------------------------------------------------
-
+    
             Arg::new(params::ARG_PROGRESSBAR_LENGTH_ID)
                 .value_name(params::ARG_PROGRESSBAR_LENGTH_NAME)
                 .short(params::ARG_PROGRESSBAR_LENGTH_SHORT_NAME)
@@ -62,27 +90,169 @@ fn main() {
                 .default_value(params::ARG_PROGRESSBAR_LENGTH_DEFAULT_STR)
                 .action(ArgAction::Set)
                 .global(true),
-
+    
             Arg::new(params::ARG_PROGRESSBAR_STYLE_ID)
                 .value_name(params::ARG_PROGRESSBAR_STYLE_NAME)
                 .short(params::ARG_PROGRESSBAR_STYLE_SHORT_NAME)
                 .long(params::ARG_PROGRESSBAR_STYLE_LONG_NAME)
                 .help(params::ARG_PROGRESSBAR_STYLE_HELP.as_str())
+                .global(true),
+    
+            Arg::new(params::ARG_FEATURE_ID)
+                .value_name(params::ARG_FEATURE_NAME)
+                .short(params::ARG_FEATURE_SHORT_NAME)
+                .long(params::ARG_FEATURE_LONG_NAME)
+                .help(params::ARG_FEATURE_HELP.as_str())
+                .global(true),
+    
+            Arg::new(params::ARG_OUTPUT_ID)
+                .value_name(params::ARG_OUTPUT_NAME)
+                .short(params::ARG_OUTPUT_SHORT_NAME)
+                .long(params::ARG_OUTPUT_LONG_NAME)
+                .help(params::ARG_OUTPUT_HELP.as_str())
+                .global(true),
+    
+            Arg::new(params::ARG_FORMAT_ID)
+                .value_name(params::ARG_FORMAT_NAME)
+                .long(params::ARG_FORMAT_LONG_NAME)
+                .help(params::ARG_FORMAT_HELP.as_str())
+                .global(true),
+    
+            Arg::new(params::ARG_FADE_ID)
+                .value_name(params::ARG_FADE_NAME)
+                .long(params::ARG_FADE_LONG_NAME)
+                .help(params::ARG_FADE_HELP)
+                .global(true),
+    
+            Arg::new(params::ARG_EASING_ID)
+                .value_name(params::ARG_EASING_NAME)
+                .long(params::ARG_EASING_LONG_NAME)
+                .help(params::ARG_EASING_HELP.as_str())
+                .global(true),
+    
+            Arg::new(params::ARG_BACKEND_ID)
+                .value_name(params::ARG_BACKEND_NAME)
+                .short(params::ARG_BACKEND_SHORT_NAME)
+                .long(params::ARG_BACKEND_LONG_NAME)
+                .env(params::ARG_BACKEND_ENV)
+                .help(params::ARG_BACKEND_HELP.as_str())
+                .global(true),
+    
+            Arg::new(params::ARG_CONFIG_ID)
+                .value_name(params::ARG_CONFIG_NAME)
+                .short(params::ARG_CONFIG_SHORT_NAME)
+                .long(params::ARG_CONFIG_LONG_NAME)
+                .env(params::ARG_CONFIG_ENV)
+                .help(params::ARG_CONFIG_HELP)
                 .global(true)
-        ]).get_matches();
+        ])
+        .subcommand(
+            Command::new(params::SUBCOMMAND_LIST_NAME)
+                .about(params::SUBCOMMAND_LIST_HELP)
+        )
+        .subcommand(
+            Command::new(params::SUBCOMMAND_SCENE_NAME)
+                .about(params::SUBCOMMAND_SCENE_HELP)
+                .arg(Arg::new(params::ARG_SCENE_ID).value_name(params::ARG_SCENE_NAME).help(params::ARG_SCENE_HELP))
+                .subcommand(
+                    Command::new(params::SUBCOMMAND_SCENE_SAVE_NAME)
+                        .about(params::SUBCOMMAND_SCENE_SAVE_HELP)
+                        .arg(Arg::new(params::ARG_SCENE_ID).value_name(params::ARG_SCENE_NAME).help(params::ARG_SCENE_HELP).required(true))
+                )
+                .args_conflicts_with_subcommands(true)
+                .arg_required_else_help(true)
+        )
+        .subcommand(
+            Command::new(params::SUBCOMMAND_BAR_NAME)
+                .about(params::SUBCOMMAND_BAR_HELP)
+                .arg(Arg::new(params::ARG_BAR_ID).value_name(params::ARG_BAR_NAME).help(params::ARG_BAR_HELP.as_str()).required(true))
+                .arg(Arg::new(params::ARG_BAR_SCOPE_ID).value_name(params::ARG_BAR_SCOPE_NAME).help(params::ARG_BAR_SCOPE_HELP))
+                .arg(
+                    Arg::new(params::ARG_SCROLL_ID)
+                        .value_name(params::ARG_SCROLL_NAME)
+                        .long(params::ARG_SCROLL_LONG_NAME)
+                        .help(params::ARG_SCROLL_HELP)
+                )
+                .arg(
+                    Arg::new(params::ARG_STEP_ID)
+                        .value_name(params::ARG_STEP_NAME)
+                        .long(params::ARG_STEP_LONG_NAME)
+                        .help(params::ARG_STEP_HELP)
+                )
+                .arg(
+                    Arg::new(params::FLAG_FOLLOW_ID)
+                        .long(params::FLAG_FOLLOW_LONG_NAME)
+                        .help(params::FLAG_FOLLOW_HELP)
+                        .action(ArgAction::SetTrue)
+                )
+        )
+        .subcommand(
+            Command::new(params::SUBCOMMAND_WATCH_NAME)
+                .about(params::SUBCOMMAND_WATCH_HELP)
+                .arg(Arg::new(params::ARG_WATCH_SCOPE_ID).value_name(params::ARG_WATCH_SCOPE_NAME).help(params::ARG_WATCH_SCOPE_HELP))
+                .arg(
+                    Arg::new(params::ARG_INTERVAL_ID)
+                        .value_name(params::ARG_INTERVAL_NAME)
+                        .long(params::ARG_INTERVAL_LONG_NAME)
+                        .help(params::ARG_INTERVAL_HELP)
+                )
+        )
+        .subcommand(
+            Command::new(params::SUBCOMMAND_INTERACTIVE_NAME)
+                .about(params::SUBCOMMAND_INTERACTIVE_HELP)
+        )
+        .subcommand(
+            Command::new(params::SUBCOMMAND_UNDO_NAME)
+                .about(params::SUBCOMMAND_UNDO_HELP)
+        )
+        .subcommand(
+            Command::new(params::SUBCOMMAND_RESTORE_NAME)
+                .about(params::SUBCOMMAND_RESTORE_HELP)
+        )
+}
+
+fn main() {
+
+    // quickfix: clap color errors
+    // clap can print error message in color
+    // before logger even starts
+    if let Some(_) = std::env::args_os().find(|arg| arg.to_ascii_lowercase() == "--nocolor") {
+        std::env::set_var("NO_COLOR", "1");
+        std::env::set_var("CLICOLOR", "0");
+    }
+
+    let cli_matches: ArgMatches = command().get_matches();
 
     // init logger and check for errors
     if let Err(err) = Logger::default()
         .colored(cli_matches.get_flag(params::FLAG_COLOR_ID))
         .verbose(cli_matches.get_flag(params::FLAG_VERBOSE_ID))
+        .stderr(
+            cli_matches.get_one::<String>(params::ARG_OUTPUT_ID).is_some_and(|format| !format.trim().eq_ignore_ascii_case("text"))
+            || cli_matches.contains_id(params::ARG_FORMAT_ID)
+            || cli_matches.subcommand_name() == Some(params::SUBCOMMAND_BAR_NAME)
+        )
         .init_logger()
     {
         eprintln!("{}", err);
         return;
     }
 
+    // subcommands share global flags with the main command
+    let (subcommand, matches): (Option<&str>, &ArgMatches) = match cli_matches.subcommand() {
+        Some((name, subcommand_matches)) => (Some(name), subcommand_matches),
+        None => (None, &cli_matches)
+    };
+
+    // `monb 50 list` would silently drop `50`
+    if let (Some(name), Some(mut bargs)) = (subcommand, cli_matches.get_many::<String>(params::ARG_BARGS_ID)) {
+        error!("Brightness argument `{}` can't be combined with the `{}` subcommand", bargs.next().expect("At least one brightness argument"), name);
+        debug!("Exiting...");
+        return;
+    }
+
     // extract brigntness arguments
-    let args: Arguments = match Arguments::try_from(&cli_matches) {
+    let args: Arguments = match Arguments::try_from(matches) {
         Ok(a) => a,
         Err(err) => {
             error!("{}", err);
@@ -91,10 +261,108 @@ fn main() {
         }
     };
 
-    // apply brigntness arguments
-    if let Err(err) = apply_arguments(args) {
+    let result: Result<(), String> = match subcommand {
+        Some(params::SUBCOMMAND_LIST_NAME) => list_monitors(args),
+        Some(params::SUBCOMMAND_SCENE_NAME) => match matches.subcommand() {
+            Some((_, save_matches)) => save_scene(args, save_matches.get_one::<String>(params::ARG_SCENE_ID).expect("Scene name is required")),
+            None => apply_scene(args, matches.get_one::<String>(params::ARG_SCENE_ID))
+        },
+        Some(params::SUBCOMMAND_BAR_NAME) => StatusBarInfo::try_from(matches).and_then(|info| run_status_bar(args, info)),
+        Some(params::SUBCOMMAND_WATCH_NAME) => WatchInfo::try_from(matches).and_then(|info| watch(args, info)),
+        Some(params::SUBCOMMAND_INTERACTIVE_NAME) => run_interactive(args),
+        Some(params::SUBCOMMAND_UNDO_NAME) => restore_state(args, true),
+        Some(params::SUBCOMMAND_RESTORE_NAME) => restore_state(args, false),
+
+        // apply brigntness arguments
+        _ => apply_arguments(args)
+    };
+
+    if let Err(err) = result {
         error!("{}", err);
     };
 
     debug!("Exiting...");
 }
+
+// arguments as parsed from a command line, for tests of other modules
+#[cfg(test)]
+fn arguments(args: &[&str]) -> Arguments {
+    let matches: ArgMatches = command().try_get_matches_from(args).unwrap();
+    return Arguments::try_from(&matches).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subcommand(args: &[&str]) -> Option<String> {
+        let matches: ArgMatches = command().try_get_matches_from(args).unwrap();
+        assert!(matches.get_many::<String>(params::ARG_BARGS_ID).is_none(), "{:?} was parsed as brightness arguments", args);
+        return matches.subcommand_name().map(String::from);
+    }
+
+    #[test]
+    fn command_is_valid() {
+        command().debug_assert();
+    }
+
+    #[test]
+    fn global_flags_work_in_front_of_subcommands() {
+        assert_eq!(subcommand(&["monb", "-v", "list"]), Some(params::SUBCOMMAND_LIST_NAME.into()));
+        assert_eq!(subcommand(&["monb", "-o", "json", "scene", "x"]), Some(params::SUBCOMMAND_SCENE_NAME.into()));
+    }
+
+    #[test]
+    fn undo_and_restore_take_global_flags() {
+        assert_eq!(subcommand(&["monb", "-v", "undo"]), Some(params::SUBCOMMAND_UNDO_NAME.into()));
+        assert_eq!(subcommand(&["monb", "--raw", "restore"]), Some(params::SUBCOMMAND_RESTORE_NAME.into()));
+        assert_eq!(subcommand(&["monb", "--raw", "-z", "undo"]), Some(params::SUBCOMMAND_UNDO_NAME.into()));
+    }
+
+    #[test]
+    fn scenes_take_global_flags() {
+        assert_eq!(subcommand(&["monb", "-c", "other.toml", "scene", "night"]), Some(params::SUBCOMMAND_SCENE_NAME.into()));
+
+        let matches: ArgMatches = command().try_get_matches_from(["monb", "-p", "scene", "save", "night"]).unwrap();
+        let (_, scene_matches) = matches.subcommand().unwrap();
+        let (save, save_matches) = scene_matches.subcommand().unwrap();
+        assert_eq!(save, params::SUBCOMMAND_SCENE_SAVE_NAME);
+        assert_eq!(save_matches.get_one::<String>(params::ARG_SCENE_ID), Some(&"night".to_string()));
+        assert!(save_matches.get_flag(params::FLAG_PERCEPTUAL_ID));
+    }
+
+    #[test]
+    fn status_bars_take_global_flags() {
+        assert_eq!(subcommand(&["monb", "--nocolor", "bar", "waybar"]), Some(params::SUBCOMMAND_BAR_NAME.into()));
+        assert_eq!(subcommand(&["monb", "-b", "fake", "bar", "i3blocks"]), Some(params::SUBCOMMAND_BAR_NAME.into()));
+    }
+
+    #[test]
+    fn watch_takes_global_flags() {
+        assert_eq!(subcommand(&["monb", "-s", "arrow", "watch"]), Some(params::SUBCOMMAND_WATCH_NAME.into()));
+        assert_eq!(subcommand(&["monb", "-v", "watch", "1-2", "--interval", "2s"]), Some(params::SUBCOMMAND_WATCH_NAME.into()));
+    }
+
+    #[test]
+    fn interactive_takes_global_flags() {
+        assert_eq!(subcommand(&["monb", "--nocolor", "interactive"]), Some(params::SUBCOMMAND_INTERACTIVE_NAME.into()));
+        assert_eq!(subcommand(&["monb", "-p", "interactive"]), Some(params::SUBCOMMAND_INTERACTIVE_NAME.into()));
+    }
+
+    #[test]
+    fn global_flags_are_shared_with_subcommands() {
+        let matches: ArgMatches = command().try_get_matches_from(["monb", "-o", "json", "scene", "x"]).unwrap();
+        let (_, scene_matches) = matches.subcommand().unwrap();
+
+        assert_eq!(scene_matches.get_one::<String>(params::ARG_OUTPUT_ID), Some(&"json".to_string()));
+        assert_eq!(scene_matches.get_one::<String>(params::ARG_SCENE_ID), Some(&"x".to_string()));
+    }
+
+    #[test]
+    fn brightness_arguments_are_still_parsed() {
+        let matches: ArgMatches = command().try_get_matches_from(["monb", "-v", "1:50", "-10"]).unwrap();
+
+        assert_eq!(matches.subcommand_name(), None);
+        assert_eq!(matches.get_many::<String>(params::ARG_BARGS_ID).unwrap().collect::<Vec<&String>>(), vec!["1:50", "-10"]);
+    }
+}
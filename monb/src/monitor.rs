@@ -1,17 +1,26 @@
+use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
+use std::thread;
 
-use brightness::Error;
-use brightness::blocking::{brightness_devices, BrightnessDevice, Brightness};
 use log::{error, info, warn, debug};
 
-use crate::cli::params::{BAction, BArg, BScope, Arguments, Getter};
+use crate::backend::{BackendDevice, BrightnessBackend, DeviceError, MonitorKind};
+use crate::calibration::Calibration;
+use crate::config::GLOBAL_LIMITS;
+use crate::limits::Limits;
+use crate::state::{Entry, State};
+use crate::output::{OutputFormat, Record, Status};
+use crate::template::{Template, TemplateValues};
+use crate::edid::Edid;
+use crate::cli::params::{BAction, BArg, BScope, BValue, Arguments, Getter};
+use crate::fade::{self, Fade};
+use crate::perceptual;
+use crate::signal;
+use crate::feature::Feature;
 use crate::progressbar::{ProgressBarInfo, create_progressbar};
 
-#[cfg(windows)]
-use crate::monitor::windows::get_device_name;
-
 #[cfg(target_os = "linux")]
-use crate::monitor::linux::get_device_name;
+use crate::backend::logind;
 
 #[macro_export]
 macro_rules! ternary_operator {
@@ -24,59 +33,222 @@ macro_rules! ternary_operator {
     };
 }
 
+const GETTER_RECORD: &'static str = "get";
+const SETTER_RECORD: &'static str = "set";
+
 #[derive(Debug)]
 pub struct Monitor {
     pub name: String,
-    pub device: BrightnessDevice
+    pub device: Box<dyn BackendDevice>,
+
+    // stable identifiers, read once during enumeration
+    pub id: String,
+    pub connector: Option<String>,
+    pub serial: Option<String>,
+    pub kind: MonitorKind,
+
+    pub feature: Feature,
+
+    // relative steps and progressbars use the perceptual (CIE L*) scale
+    pub perceptual: bool,
+
+    // maps logical brightness to device brightness, getters and setters only see logical values
+    pub calibration: Option<Calibration>,
+
+    // floor and ceiling of logical values, in percentages
+    pub limits: Limits,
+
+    // only used in structured output records
+    pub index: usize,
+    pub backend: &'static str,
+    pub output: OutputFormat
 }
 
 impl Monitor {
-    fn print(&self, progressbar_info: &ProgressBarInfo) {
-        let mut current_brightness: u32 = match self.device.get() {
-            Ok(b) => b,
-            Err(err) => { error!("{} - {}", self.name, err.to_string()); return; }
-        };
-        if current_brightness > 100 {
-            debug!("Monitor returned brightness `{}`, clamping to 100", current_brightness);
-            current_brightness = 100;
+    pub fn get(&self) -> Result<u32, String> {
+        let value: u32 = self.device.get(self.feature)?;
+        return Ok(match &self.calibration {
+            Some(calibration) => calibration.to_logical(value),
+            None => value
+        });
+    }
+
+    pub fn set(&self, value: u32) -> Result<(), DeviceError> {
+        self.device.set(self.feature, self.device_value(value))
+    }
+
+    /// Keys identifying this monitor in scopes and the configuration, from the most to the least specific:
+    /// `@serial`, identity and connector.
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = Vec::<String>::new();
+        if let Some(serial) = &self.serial {
+            keys.push(format!("@{}", serial));
+        }
+        if !self.id.is_empty() {
+            keys.push(self.id.clone());
+        }
+        if let Some(connector) = self.connector.as_ref().filter(|connector| **connector != self.id) {
+            keys.push(connector.clone());
+        }
+
+        return keys;
+    }
+
+    fn matches(&self, scope: &BScope) -> bool {
+        let key: String = match scope {
+            BScope::Id(_) | BScope::Serial(_) => scope.to_string(),
+            _ => return false
         };
 
-        info!("{} - {}", self.name, create_progressbar(current_brightness, progressbar_info));
+        self.keys().iter().any(|k| k.eq_ignore_ascii_case(&key))
+    }
+
+    fn device_value(&self, value: u32) -> u32 {
+        match &self.calibration {
+            Some(calibration) => calibration.to_device(value),
+            None => value
+        }
+    }
+
+    pub fn max(&self) -> u32 {
+        self.device.max(self.feature)
+    }
+
+    /// Limits on the scale of `max`, `None` if the monitor has no limits.
+    pub fn bounds(&self) -> Option<(u32, u32)> {
+        match self.limits.is_empty() {
+            true => None,
+            false => Some(self.limits.bounds(self.max()))
+        }
+    }
+
+    /// Value as shown by progressbars, a percentage of `max` or the perceptual lightness.
+    pub fn percentage(&self, value: u32, max: u32) -> u32 {
+        if self.perceptual {
+            perceptual::to_perceptual(value, max)
+        } else if max == 100 || self.feature != Feature::Brightness {
+            value.min(100)
+        } else {
+            (value as u64 * 100 / max.max(1) as u64) as u32
+        }
+    }
+
+    // structured record of a getter or a setter, text output is logged instead
+    fn report(&self, kind: &'static str, previous: Option<u32>, value: Option<u32>, status: Status, error: Option<String>) {
+        if !self.output.is_structured() {
+            return;
+        }
+
+        let bounds: Option<(u32, u32)> = self.bounds();
+        Record {
+            kind,
+            index: self.index,
+            id: &self.id,
+            name: &self.name,
+            backend: self.backend,
+            feature: self.feature.into(),
+            previous,
+            value,
+            max: self.max(),
+            limit_min: bounds.filter(|_| self.limits.min.is_some()).map(|(floor, _)| floor),
+            limit_max: bounds.filter(|_| self.limits.max.is_some()).map(|(_, ceiling)| ceiling),
+            status,
+            error
+        }.print(self.output);
     }
-}
 
-#[cfg(windows)]
-mod windows {
-    use brightness::blocking::BrightnessDevice;
-    use brightness::blocking::windows::BrightnessExt;
-    use log::debug;
+    // saving the state never stops a change, `undo` just won't know about it
+    fn record_state(&self, previous: u32, current: u32) {
+        if let Err(err) = State::record(&self.keys(), self.feature, previous, current, self.max()) {
+            warn!("{} - failed to save {} state ({})", self.name, self.feature.description(), err);
+        }
+    }
 
-    pub fn get_device_name(device: &BrightnessDevice, index: usize) -> String {
-        match device.device_description() {
-            Ok(device_description) => format!("Monitor #{} ({})", index, device_description),
+    // ` (minimum limit)` if the value sits at a limit, so getters explain why it doesn't go any further
+    fn limit_note(&self, value: u32) -> &'static str {
+        match self.bounds() {
+            Some((floor, _)) if self.limits.min.is_some() && value <= floor => " (minimum limit)",
+            Some((_, ceiling)) if self.limits.max.is_some() && value >= ceiling => " (maximum limit)",
+            _ => ""
+        }
+    }
+
+    fn print(&self, progressbar_info: &ProgressBarInfo, template: Option<&Template>) {
+        let max_brightness: u32 = self.max();
+        let mut current_brightness: u32 = match self.get() {
+            Ok(b) => b,
             Err(err) => {
-                debug!("Monitor #{} - {}", index, err.to_string());
-                format!("Monitor #{}", index)
+                error!("{} - {}", self.name, err);
+                self.report(GETTER_RECORD, None, None, Status::Error, Some(err));
+                return;
             }
+        };
+        if current_brightness > max_brightness {
+            debug!("Monitor returned {} `{}`, clamping to {}", self.feature.description(), current_brightness, max_brightness);
+            current_brightness = max_brightness;
+        };
+
+        if self.output.is_structured() {
+            self.report(GETTER_RECORD, None, Some(current_brightness), Status::Ok, None);
+            return;
         }
+
+        let line: String = self.line(current_brightness, max_brightness, progressbar_info, template);
+        match template {
+            Some(_) => println!("{}", line),
+            None => info!("{}", line)
+        };
     }
-}
 
+    /// Getter line of a value, rendered with the template if there is one.
+    pub fn line(&self, value: u32, max: u32, progressbar_info: &ProgressBarInfo, template: Option<&Template>) -> String {
+        let bar_value: u32 = self.percentage(value, max);
 
-#[cfg(target_os = "linux")]
-mod linux {
-    use brightness::blocking::BrightnessDevice;
+        if let Some(t) = template {
+            // input source and power mode are codes, a progressbar doesn't make sense for them
+            let bar: String = ternary_operator!(self.feature.is_continuous(), create_progressbar(bar_value, progressbar_info), String::new());
+            return t.render(&TemplateValues {
+                index: self.index,
+                name: &self.name,
+                id: &self.id,
+                value,
+                percent: bar_value,
+                bar: &bar
+            });
+        }
 
-    pub fn get_device_name(_: &BrightnessDevice, index: usize) -> String {
-        return format!("Monitor #{}", index);
+        let limit_note: &'static str = self.limit_note(value);
+        if self.perceptual {
+            format!("{} - {} (perceptual, {}/{}){}", self.name, create_progressbar(bar_value, progressbar_info), value, max, limit_note)
+        } else if !self.feature.is_continuous() {
+            // input source and power mode are codes, a progressbar doesn't make sense for them
+            format!("{} - {} `0x{:02X}`", self.name, self.feature.description(), value)
+        } else if self.feature != Feature::Brightness {
+            format!("{} - {} {}", self.name, self.feature, create_progressbar(bar_value, progressbar_info))
+        } else if max == 100 {
+            format!("{} - {}{}", self.name, create_progressbar(bar_value, progressbar_info), limit_note)
+        } else {
+            // raw device values, the progressbar is always drawn in percentages
+            format!("{} - {} ({}/{}){}", self.name, create_progressbar(bar_value, progressbar_info), value, max, limit_note)
+        }
     }
 }
 
+/// Current values of the monitors at `indices`, clamped to their maximum. Errors are kept, so callers can show them in place.
+pub fn read_values(devices: &HashMap<usize, Monitor>, indices: &[usize]) -> Vec<(usize, Result<u32, String>)> {
+    indices.iter()
+        .map(|index| {
+            let monitor: &Monitor = &devices[index];
+            (*index, monitor.get().map(|value| value.min(monitor.max())))
+        })
+        .collect()
+}
 
-pub fn get_all_devices(flag_zero: bool) -> Result<HashMap<usize, Monitor>, String> {
+pub fn get_all_devices(backend: &dyn BrightnessBackend, arguments: &Arguments) -> Result<HashMap<usize, Monitor>, String> {
     let mut devices: HashMap<usize, Monitor> = HashMap::<usize, Monitor>::new();
 
-    let potential_devices: Vec<Result<BrightnessDevice, Error>> = brightness_devices().collect();
+    debug!("Enumerating monitors using `{}` backend", backend.name());
+    let potential_devices: Vec<Result<Box<dyn BackendDevice>, String>> = backend.enumerate();
     let potential_devices_len: usize = potential_devices.len();
     if potential_devices_len == 0 {
         return Err("No monitors found :(".into());
@@ -85,19 +257,68 @@ pub fn get_all_devices(flag_zero: bool) -> Result<HashMap<usize, Monitor>, Strin
     }
 
     // if zero flags is enabled do not offset, else offset by 1
-    let zero_offset = ternary_operator!(flag_zero, 0, 1);
+    let zero_offset = ternary_operator!(arguments.flag_zero, 0, 1);
+
+    // perceptual scale, calibration and limits only make sense for brightness
+    let perceptual: bool = arguments.flag_perceptual && arguments.feature == Feature::Brightness;
+    let limited: bool = arguments.feature == Feature::Brightness;
+    let global_limits: Limits = arguments.config.limits(GLOBAL_LIMITS).copied().unwrap_or_default();
+    let calibrated: bool = arguments.feature == Feature::Brightness && !(arguments.flag_raw && arguments.backend.supports_raw());
 
     for (mut index, potential_device) in potential_devices.into_iter().enumerate() {
         index += zero_offset;
 
         match potential_device {
             Ok(device) => {
-                devices.insert(
+                let mut monitor = Monitor {
+                    name: device.describe(index),
+                    id: device.id(),
+                    connector: device.connector(),
+                    serial: device.serial(),
+                    kind: device.kind(),
+                    device,
+                    feature: arguments.feature,
+                    perceptual,
+                    calibration: None,
+                    limits: Limits::default(),
                     index,
-                    Monitor { name: get_device_name(&device, index), device }
-                );
+                    backend: backend.name(),
+                    output: arguments.output
+                };
+                debug!("{} - {}, identifiers: {}", monitor.name, monitor.kind, monitor.keys().join(", "));
+
+                let profile: Option<(String, &Calibration)> = monitor.keys().into_iter()
+                    .find_map(|key| arguments.config.calibration(&key).map(|calibration| (key, calibration)));
+
+                match profile {
+                    Some((key, calibration)) if calibrated => {
+                        debug!("{} - using calibration profile `{}`", monitor.name, key);
+                        monitor.calibration = Some(calibration.clone());
+                    },
+                    Some((key, _)) => warn!("{} - calibration profile `{}` ignored, it only applies to brightness percentages", monitor.name, key),
+                    None => {}
+                };
+
+                if limited {
+                    let monitor_limits: Option<(String, Limits)> = monitor.keys().into_iter()
+                        .find_map(|key| arguments.config.limits(&key).map(|limits| (key, *limits)));
+
+                    monitor.limits = match monitor_limits {
+                        Some((key, limits)) => {
+                            debug!("{} - using limits `{}`", monitor.name, key);
+                            let merged: Limits = limits.or(global_limits);
+                            if let Err(err) = merged.validate() {
+                                warn!("{} - limits `{}` combined with `{}` are inconsistent, the minimum wins ({})", monitor.name, key, GLOBAL_LIMITS, err);
+                            }
+                            merged
+                        },
+                        None => global_limits
+                    };
+                }
+
+                devices.insert(index, monitor);
             },
-            Err(err) => error!("Monitor #{} - {}", index, err.to_string())
+            Err(err) => error!("Monitor #{} - {}", index, err)
         }
     };
 
@@ -105,6 +326,8 @@ pub fn get_all_devices(flag_zero: bool) -> Result<HashMap<usize, Monitor>, Strin
         return Err("No valid monitors found :(".into());
     }
 
+    apply_alias_names(&mut devices, arguments);
+
     let devices_len: usize = devices.len();
     if devices_len == potential_devices_len {
         debug!("All monitors are valid");
@@ -115,20 +338,116 @@ pub fn get_all_devices(flag_zero: bool) -> Result<HashMap<usize, Monitor>, Strin
     return Ok(devices);
 }
 
-pub fn apply_setter_barg(monitor: &Monitor, barg: &BArg) -> Result<(), String> {
+// fallback for unprivileged users, returns `false` if the session can't set the brightness of this monitor
+#[cfg(target_os = "linux")]
+fn set_through_session(monitor: &Monitor, desired_brightness: u32) -> Result<bool, String> {
+    if monitor.feature != Feature::Brightness {
+        return Ok(false);
+    }
+
+    let target = match monitor.device.logind_target(monitor.device_value(desired_brightness))? {
+        Some(t) => t,
+        None => return Ok(false)
+    };
+
+    let session = match logind::session() {
+        Some(s) => s,
+        None => return Ok(false)
+    };
+
+    session.set_brightness(&target)?;
+    return Ok(true);
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_through_session(_: &Monitor, _: u32) -> Result<bool, String> {
+    return Ok(false);
+}
+
+// `percentage` percent of `value`, rounded to the nearest unit but at least one unit,
+// so repeated small steps can still leave (or reach) the dark end of the scale
+fn relative_step(value: u32, percentage: u32) -> u32 {
+    if percentage == 0 {
+        return 0;
+    }
+
+    let step: u64 = (value as u64 * percentage as u64 + 50) / 100;
+    return step.clamp(1, u32::MAX as u64) as u32;
+}
+
+// readings drift (especially over DDC/CI), values within 2% of the range count as equal
+fn step_tolerance(max: u32) -> u32 {
+    (max as u64 * 2).div_ceil(100).max(1) as u32
+}
+
+fn is_near(value: u32, step: u32, tolerance: u32) -> bool {
+    value.abs_diff(step) <= tolerance
+}
+
+// second value if the current one is near the first, the first value otherwise
+fn toggle_step(value: u32, steps: &[u32], tolerance: u32) -> u32 {
+    match steps {
+        [first, second, ..] if is_near(value, *first, tolerance) => *second,
+        [first, ..] => *first,
+        [] => value
+    }
+}
+
+// value after the nearest matching one, wrapping around; without a match the first value above the current one
+fn cycle_step(value: u32, steps: &[u32], tolerance: u32) -> u32 {
+    let matching: Option<usize> = steps.iter()
+        .enumerate()
+        .filter(|(_, step)| is_near(value, **step, tolerance))
+        .min_by_key(|(_, step)| value.abs_diff(**step))
+        .map(|(index, _)| index);
+
+    match matching {
+        Some(index) => steps[(index + 1) % steps.len()],
+        None => steps.iter().copied().find(|step| *step > value).or(steps.first().copied()).unwrap_or(value)
+    }
+}
 
-    let mut current_brightness: u32 = match monitor.device.get() {
+/// Errors are reported, but not logged, callers decide how to show them.
+pub fn apply_setter_barg(monitor: &Monitor, barg: &BArg, fade: Option<&Fade>) -> Result<(), String> {
+    let max_brightness: u32 = monitor.max();
+    let mut current_brightness: u32 = match monitor.get() {
         Ok(b) => b,
-        Err(err) => return Err(format!("{} - {}", monitor.name, err.to_string()))
+        Err(err) => {
+            let message: String = format!("{} - {}", monitor.name, err);
+            monitor.report(SETTER_RECORD, None, None, Status::Error, Some(message.clone()));
+            return Err(message);
+        }
     };
-    if current_brightness > 100 {
-        debug!("Monitor returned brightness `{}`, clamping to 100", current_brightness);
-        current_brightness = 100;
+    if current_brightness > max_brightness {
+        debug!("Monitor returned brightness `{}`, clamping to {}", current_brightness, max_brightness);
+        current_brightness = max_brightness;
     };
 
+    // a failed change leaves the monitor where it was
+    let result: Result<(), String> = set_monitor(monitor, barg, fade, current_brightness);
+    if let Err(err) = &result {
+        monitor.report(SETTER_RECORD, Some(current_brightness), Some(current_brightness), Status::Error, Some(err.clone()));
+    }
+
+    return result;
+}
+
+fn set_monitor(monitor: &Monitor, barg: &BArg, fade: Option<&Fade>, current_brightness: u32) -> Result<(), String> {
+    let max_brightness: u32 = monitor.max();
     let barg_scope: String = barg.scope.to_string();
     let bvalue_brightness: Option<usize> = barg.value.brightness;
 
+    // with `--perceptual` conditions are compared against the perceptual percentage, the same value the progressbar shows
+    if let Some(condition) = &barg.condition {
+        let compared: u32 = ternary_operator!(monitor.perceptual, monitor.percentage(current_brightness, max_brightness), current_brightness);
+        if !condition.is_met(compared) {
+            let scale: &'static str = ternary_operator!(monitor.perceptual, " (perceptual)", "");
+            warn!("{} - skipping, {} `{}`{} doesn't match condition `{}`", monitor.name, monitor.feature.description(), compared, scale, condition.to_string());
+            monitor.report(SETTER_RECORD, Some(current_brightness), Some(current_brightness), Status::Skipped, None);
+            return Ok(());
+        }
+    }
+
     let mut desired_brightness: u32 = match barg.value.action {
 
         BAction::Dec => {
@@ -137,12 +456,16 @@ pub fn apply_setter_barg(monitor: &Monitor, barg: &BArg) -> Result<(), String> {
                 None => return Err(format!("Unexpected error, brightness increment value for scope `{}` was `None`", barg_scope))
             };
 
-            let (desired_brightness, overflow) = current_brightness.overflowing_sub(desired_decrement_unclamped);
-            if overflow { // u32 subtraction overflow
-                debug!("Desired brightness value for scope `{}` was smaller than 0, clamping to 0", barg_scope);
-                0
+            if monitor.perceptual {
+                perceptual::step(current_brightness, max_brightness, -(desired_decrement_unclamped as i64))
             } else {
-                desired_brightness
+                let (desired_brightness, overflow) = current_brightness.overflowing_sub(desired_decrement_unclamped);
+                if overflow { // u32 subtraction overflow
+                    debug!("Desired brightness value for scope `{}` was smaller than 0, clamping to 0", barg_scope);
+                    0
+                } else {
+                    desired_brightness
+                }
             }
         },
 
@@ -152,12 +475,16 @@ pub fn apply_setter_barg(monitor: &Monitor, barg: &BArg) -> Result<(), String> {
                 None => return Err(format!("Unexpected error, brightness decrement value for scope `{}` was `None`", barg_scope))
             };
 
-            let (desired_brightness, overflow) = current_brightness.overflowing_add(desired_increment_unclamped);
-            if overflow {
-                debug!("Desired brightness value for scope `{}` was bigger than 100, clamping to 100", barg_scope);
-                100
+            if monitor.perceptual {
+                perceptual::step(current_brightness, max_brightness, desired_increment_unclamped as i64)
             } else {
-                desired_brightness
+                let (desired_brightness, overflow) = current_brightness.overflowing_add(desired_increment_unclamped);
+                if overflow {
+                    debug!("Desired brightness value for scope `{}` was bigger than {}, clamping to {}", barg_scope, max_brightness, max_brightness);
+                    max_brightness
+                } else {
+                    desired_brightness
+                }
             }
         },
 
@@ -167,90 +494,387 @@ pub fn apply_setter_barg(monitor: &Monitor, barg: &BArg) -> Result<(), String> {
                 None => return Err(format!("Unexpected error, brightness increment value for scope `{}` was `None`", barg_scope))
             };
 
-            if desired_brightness_value > 100 {
-                debug!("Desired brightness value for scope `{}` was bigger than 100, clamping to 100", barg_scope);
-                100
+            // with `--perceptual` exact values are perceptual percentages, the same scale getters and conditions use
+            if monitor.perceptual {
+                perceptual::from_perceptual(desired_brightness_value.min(100), max_brightness)
+            } else if desired_brightness_value > max_brightness {
+                debug!("Desired brightness value for scope `{}` was bigger than {}, clamping to {}", barg_scope, max_brightness, max_brightness);
+                max_brightness
             } else {
                 desired_brightness_value
             }
         },
 
+        // multiplying and relative percentages scale the value itself, they are proportional on any scale
+        BAction::Mul => {
+            let factor: f64 = match barg.value.factor {
+                Some(f) => f,
+                None => return Err(format!("Unexpected error, brightness multiplier for scope `{}` was `None`", barg_scope))
+            };
+
+            let desired_brightness: f64 = (current_brightness as f64 * factor).round();
+            if desired_brightness > max_brightness as f64 {
+                debug!("Desired brightness value for scope `{}` was bigger than {}, clamping to {}", barg_scope, max_brightness, max_brightness);
+                max_brightness
+            } else {
+                desired_brightness as u32
+            }
+        },
+
+        BAction::IncPercent | BAction::DecPercent => {
+            let percentage: u32 = match bvalue_brightness {
+                Some(b) => b.min(u32::MAX as usize) as u32,
+                None => return Err(format!("Unexpected error, relative brightness percentage for scope `{}` was `None`", barg_scope))
+            };
+
+            let step: u32 = relative_step(current_brightness, percentage);
+            if barg.value.action == BAction::IncPercent {
+                current_brightness.saturating_add(step)
+            } else {
+                current_brightness.saturating_sub(step)
+            }
+        },
+
+        BAction::Toggle | BAction::Cycle => {
+            // codes (input sources, power modes) don't drift and neighbouring codes are different settings
+            let tolerance: u32 = ternary_operator!(monitor.feature.is_continuous(), step_tolerance(max_brightness), 0);
+            let steps: Vec<u32> = barg.value.steps.iter()
+                .map(|step| ternary_operator!(monitor.perceptual, perceptual::from_perceptual((*step as u32).min(100), max_brightness), *step as u32))
+                .collect();
+
+            match barg.value.action {
+                BAction::Toggle => toggle_step(current_brightness, &steps, tolerance),
+                _ => cycle_step(current_brightness, &steps, tolerance)
+            }
+        },
+
         BAction::Get => return Err(format!("Unexpected error, brightness decrement value for scope `{}` was `None`", barg_scope))
     };
 
+    // relative changes stop at the condition bound, absolute values are set as they are
+    if let (Some(condition), BAction::Inc | BAction::Dec | BAction::IncPercent | BAction::DecPercent | BAction::Mul) = (&barg.condition, &barg.value.action) {
+        let bounded_brightness: u32 = if monitor.perceptual {
+            let lightness: u32 = monitor.percentage(desired_brightness, max_brightness);
+            let bounded_lightness: u32 = condition.clamp(lightness);
+            ternary_operator!(bounded_lightness == lightness, desired_brightness, perceptual::from_perceptual(bounded_lightness, max_brightness))
+        } else {
+            condition.clamp(desired_brightness)
+        };
+        if bounded_brightness != desired_brightness {
+            debug!("Desired brightness value for scope `{}` crosses condition `{}`, clamping to {}", barg_scope, condition.to_string(), bounded_brightness);
+            desired_brightness = bounded_brightness;
+        }
+    }
+
+    if desired_brightness > max_brightness {
+        debug!("Monitor desired brightness `{}`, clamping to {}", desired_brightness, max_brightness);
+        desired_brightness = max_brightness;
+    };
+
+    let feature_description: &'static str = monitor.feature.description();
+
+    // configured limits win over every action, including `min`, `max` and fades
+    if let Some((floor, ceiling)) = monitor.bounds() {
+        let limited_brightness: u32 = desired_brightness.clamp(floor, ceiling);
+        if limited_brightness != desired_brightness {
+            info!("{} - desired {} `{}` is outside the limits `{}`, using `{}`", monitor.name, feature_description, desired_brightness, monitor.limits.to_string(), limited_brightness);
+            desired_brightness = limited_brightness;
+        }
+    }
+
     if current_brightness == desired_brightness {
-        warn!("{} - no need to change brightness", monitor.name);
+        warn!("{} - no need to change {}", monitor.name, feature_description);
+        monitor.report(SETTER_RECORD, Some(current_brightness), Some(current_brightness), Status::Unchanged, None);
         return Ok(());
     }
 
-    if desired_brightness > 100 {
-        debug!("Monitor desired brightness `{}`, clamping to 100", desired_brightness);
-        desired_brightness = 100;
+    // recorded before the change, so `undo` works even if monb is killed halfway through a fade
+    monitor.record_state(current_brightness, desired_brightness);
+
+    // a write without permission is retried through systemd-logind, which then takes over the rest of a fade
+    let through_session: Cell<bool> = Cell::new(false);
+    let set = |value: u32| -> Result<(), DeviceError> {
+        if through_session.get() {
+            return set_through_session(monitor, value).map(|_| ()).map_err(DeviceError::Other);
+        }
+
+        match monitor.set(value) {
+            Err(DeviceError::PermissionDenied(message)) => match set_through_session(monitor, value) {
+                Ok(true) => {
+                    debug!("{} - no permission to write {}, continuing through systemd-logind", monitor.name, feature_description);
+                    through_session.set(true);
+                    Ok(())
+                },
+                Ok(false) => Err(DeviceError::PermissionDenied(message)),
+                Err(err) => Err(DeviceError::PermissionDenied(format!("{} ({})", message, err)))
+            },
+            result => result
+        }
+    };
+
+    // errors come with the last value that was set, a fade can fail halfway
+    let set_result: Result<u32, (u32, DeviceError)> = match fade {
+        Some(f) => fade::fade(current_brightness, desired_brightness, f, set),
+        None => set(desired_brightness).map(|_| desired_brightness).map_err(|err| (current_brightness, err))
     };
 
-    match monitor.device.set(desired_brightness) {
-        Ok(_) => info!("{} - successfully set monitor brightness to `{}`", monitor.name, desired_brightness),
-        Err(err) => error!("{} - failed to set monitor brightness to `{}` ({})", monitor.name, desired_brightness, err.to_string())
+    // the state is corrected if the monitor didn't end up at the desired value
+    let reached: Result<(u32, Status), (u32, String)> = match set_result {
+        Ok(reached) if reached != desired_brightness => {
+            warn!("{} - fade interrupted, monitor {} stopped at `{}`", monitor.name, feature_description, reached);
+            Ok((reached, Status::Interrupted))
+        },
+        Ok(_) => {
+            let session_note: &'static str = ternary_operator!(through_session.get(), " through systemd-logind", "");
+            info!("{} - successfully set monitor {} to `{}`{}", monitor.name, feature_description, desired_brightness, session_note);
+            Ok((desired_brightness, Status::Ok))
+        },
+        Err((reached, DeviceError::PermissionDenied(message))) => Err((reached, format!("{} - {}", monitor.name, message))),
+        Err((reached, err)) => Err((reached, format!("{} - failed to set monitor {} to `{}` ({})", monitor.name, feature_description, desired_brightness, err)))
     };
 
+    let reached_brightness: u32 = match &reached {
+        Ok((brightness, _)) | Err((brightness, _)) => *brightness
+    };
+    if reached_brightness != desired_brightness {
+        monitor.record_state(current_brightness, reached_brightness);
+    }
+
+    let (reached_brightness, status): (u32, Status) = reached.map_err(|(_, err)| err)?;
+    monitor.report(SETTER_RECORD, Some(current_brightness), Some(reached_brightness), status, None);
+
     return Ok(());
 }
 
-pub fn apply_arguments(arguments: Arguments) -> Result<(), String> {
-    // get devices
-    let devices: HashMap<usize, Monitor> = get_all_devices(arguments.flag_zero)?;
+// sorted indices of monitors selected by a scope, warns about scopes that select nothing
+pub fn resolve_scope(devices: &HashMap<usize, Monitor>, scope: &BScope) -> Vec<usize> {
+    select_scope(devices, scope, true)
+}
 
-    let mut potential_set_global_barg: Option<&BArg> = None;
-    let mut used_setter_indices: HashSet<usize> = HashSet::<usize>::new();
+fn select_scope(devices: &HashMap<usize, Monitor>, scope: &BScope, report: bool) -> Vec<usize> {
+    let mut indices: Vec<usize> = match scope {
+        BScope::Global => devices.keys().copied().collect(),
+        BScope::Index(index) => devices.contains_key(index).then_some(*index).into_iter().collect(),
+        BScope::Id(_) | BScope::Serial(_) => devices.iter().filter(|(_, monitor)| monitor.matches(scope)).map(|(index, _)| *index).collect(),
+        BScope::Kind(kind) => devices.iter().filter(|(_, monitor)| monitor.kind == *kind).map(|(index, _)| *index).collect(),
+        BScope::Range(from, to) => devices.keys().copied().filter(|index| (*from..=*to).contains(index)).collect(),
+        BScope::List(items) => items.iter().flat_map(|item| select_scope(devices, item, report)).collect(),
+        BScope::Except(items) => {
+            let excluded: HashSet<usize> = items.iter().flat_map(|item| select_scope(devices, item, report)).collect();
+            devices.keys().copied().filter(|index| !excluded.contains(index)).collect()
+        },
+        BScope::Alias(_, aliased) => select_scope(devices, aliased, false)
+    };
+    indices.sort();
+    indices.dedup();
 
-    // apply all setters without global scope
-    // also add them to the hashset
-    for barg in &arguments.bargs.setters {
-        let index: usize = match barg.scope {
-            BScope::Global => {
-                if potential_set_global_barg.is_some() {
-                    warn!("Global brightness argument already set. Ignoring provided global argument");
-                } else {
-                    potential_set_global_barg = Some(barg);
-                }
+    if indices.is_empty() && report {
+        match scope {
+            BScope::Index(index) => warn!("Monitor with index `{}` not found. Check the `zero` flag if you think this is an error", index),
+            BScope::Alias(name, aliased) => warn!("Alias `{}` (`{}`) doesn't match any connected monitor", name, aliased.to_string()),
+            BScope::List(_) => {}, // every item was already reported
+            _ => warn!("No monitor matches scope `{}`. Use the `list` subcommand to see indices and identifiers of all monitors", scope.to_string())
+        };
+    }
+
+    return indices;
+}
+
+// monitors selected by exactly one alias are shown under that alias, the first alias wins
+fn apply_alias_names(devices: &mut HashMap<usize, Monitor>, arguments: &Arguments) {
+    let mut aliased_indices: HashSet<usize> = HashSet::<usize>::new();
 
-                continue;
+    for (name, alias) in &arguments.config.aliases {
+        let indices: Vec<usize> = select_scope(devices, &alias.0, false);
+        match indices.as_slice() {
+            [] => debug!("Alias `{}` (`{}`) doesn't match any connected monitor", name, alias.0.to_string()),
+            [index] if aliased_indices.insert(*index) => {
+                let monitor: &mut Monitor = devices.get_mut(index).expect("Selected monitor has to exist");
+                debug!("{} - shown as alias `{}`", monitor.name, name);
+                monitor.name = name.clone();
             },
-            BScope::Index(i) => i
+            _ => {}
         };
+    }
+}
+
+fn print_edid(monitor: &Monitor) {
+    let edid: Edid = match monitor.device.edid() {
+        Ok(Some(e)) => e,
+        Ok(None) => { info!("    EDID: not available"); return; },
+        Err(err) => { warn!("    EDID: {}", err); return; }
+    };
 
-        match devices.get(&index) {
-            None => warn!("Monitor with index `{}` not found. Check the `zero` flag if you think this is an error", index),
-            Some(monitor) => {
-                used_setter_indices.insert(index);
-                if let Err(err) = apply_setter_barg(monitor, &barg) {
+    info!("    manufacturer: {}", edid.manufacturer);
+    info!("    product code: 0x{:04X}", edid.product_code);
+    if let Some(model_name) = &edid.model_name {
+        info!("    model: {}", model_name);
+    }
+    if let Some(serial) = edid.serial() {
+        info!("    serial: {}", serial);
+    }
+    match edid.size {
+        Some((width, height)) => info!("    size: {} x {} cm", width, height),
+        None => info!("    size: unknown")
+    };
+    info!("    {}: {}", ternary_operator!(edid.model_year, "model year", "manufacture year"), edid.year);
+    info!("    EDID version: {}.{}", edid.version.0, edid.version.1);
+}
+
+pub fn list_monitors(arguments: Arguments) -> Result<(), String> {
+    let backend: Box<dyn BrightnessBackend> = arguments.backend.create(arguments.flag_raw)?;
+    let devices: HashMap<usize, Monitor> = get_all_devices(backend.as_ref(), &arguments)?;
+
+    let mut indices: Vec<&usize> = devices.keys().collect();
+    indices.sort();
+
+    for index in indices {
+        let monitor: &Monitor = &devices[index];
+        info!("{} - backend `{}`", monitor.name, backend.name());
+        info!("    index: {}", index);
+        info!("    kind: {}", monitor.kind);
+        info!("    identifiers: {}", monitor.keys().join(", "));
+        if !monitor.limits.is_empty() {
+            info!("    limits: {}", monitor.limits.to_string());
+        }
+        print_edid(monitor);
+    }
+
+    return Ok(());
+}
+
+// fades run in parallel so they all finish at the same moment
+fn apply_planned_setters(planned_setters: Vec<(&Monitor, &BArg)>, arguments: &Arguments) {
+    match arguments.fade_info.start() {
+        None => {
+            for (monitor, barg) in planned_setters {
+                if let Err(err) = apply_setter_barg(monitor, barg, None) {
                     error!("{}", err);
-                };
+                }
+            }
+        },
+
+        Some(fade) => {
+            signal::install_interrupt_handler();
+            thread::scope(|scope| {
+                for (monitor, barg) in planned_setters {
+                    scope.spawn(move || {
+                        if let Err(err) = apply_setter_barg(monitor, barg, Some(&fade)) {
+                            error!("{}", err);
+                        }
+                    });
+                }
+            });
+        }
+    };
+}
+
+/// `undo` sets monitors changed by the last invocation back to their previous values, running it again redoes the change.
+/// `restore` sets every monitor to its last known value.
+pub fn restore_state(arguments: Arguments, undo: bool) -> Result<(), String> {
+    let backend: Box<dyn BrightnessBackend> = arguments.backend.create(arguments.flag_raw)?;
+    let mut devices: HashMap<usize, Monitor> = get_all_devices(backend.as_ref(), &arguments)?;
+    let state: State = State::load()?;
+
+    // saved values are device values, they are set as they are even with `--perceptual`
+    devices.values_mut().for_each(|monitor| monitor.perceptual = false);
+
+    let mut indices: Vec<&usize> = devices.keys().collect();
+    indices.sort();
+
+    let monitors: Vec<(Vec<String>, Feature)> = indices.iter().map(|index| (devices[*index].keys(), devices[*index].feature)).collect();
+
+    // only the newest change of connected monitors is undone
+    let restored_bargs: Vec<(&Monitor, BArg)> = indices.into_iter()
+        .zip(state.entries(&monitors, undo))
+        .filter_map(|(index, entry)| {
+            let monitor: &Monitor = &devices[index];
+            let entry: Entry = match entry {
+                Some(entry) => entry,
+                None => {
+                    debug!("{} - no saved {} to {}", monitor.name, monitor.feature.description(), ternary_operator!(undo, "undo", "restore"));
+                    return None;
+                }
+            };
+
+            let value: u32 = entry.restored(undo, monitor.max(), monitor.feature);
+            Some((monitor, BArg::new(BScope::Index(*index), BValue::new(BAction::Set, value as usize))))
+        })
+        .collect();
+
+    if restored_bargs.is_empty() {
+        warn!("Nothing to {}, no saved {} for connected monitors", ternary_operator!(undo, "undo", "restore"), arguments.feature.description());
+        return Ok(());
+    }
+
+    apply_planned_setters(restored_bargs.iter().map(|(monitor, barg)| (*monitor, barg)).collect(), &arguments);
+    return Ok(());
+}
+
+// pair every monitor with the setter that applies to it, the most specific scope wins
+fn plan_setters<'a>(devices: &'a HashMap<usize, Monitor>, setters: &'a [BArg]) -> Vec<(&'a Monitor, &'a BArg)> {
+    let mut potential_set_global_barg: Option<&BArg> = None;
+    let mut used_setter_indices: HashSet<usize> = HashSet::<usize>::new();
+    let mut planned_setters: Vec<(&Monitor, &BArg)> = Vec::<(&Monitor, &BArg)>::new();
+
+    // plan all setters without global scope
+    // also add them to the hashset
+    // setters are already sorted by scope precedence, so the first setter selecting a monitor wins
+    for barg in setters {
+        if barg.scope == BScope::Global {
+            if potential_set_global_barg.is_some() {
+                warn!("Global brightness argument already set. Ignoring provided global argument");
+            } else {
+                potential_set_global_barg = Some(barg);
+            }
+
+            continue;
+        }
+
+        for index in resolve_scope(devices, &barg.scope) {
+            if used_setter_indices.insert(index) {
+                planned_setters.push((&devices[&index], barg));
+            } else {
+                debug!("Monitor #{} already has a brightness argument, ignoring `{}`", index, barg.to_string());
             }
         }
     }
 
     // if global setter exists
-    // apply global setter
-    // to all monitors not in hashset
+    // plan global setter
+    // for all monitors not in hashset
     if let Some(global_setter) = potential_set_global_barg {
-        for (monitor_index, monitor) in &devices {
+        for (monitor_index, monitor) in devices {
             if !used_setter_indices.contains(monitor_index) {
-                if let Err(err) = apply_setter_barg(monitor, global_setter) {
-                    error!("{}", err);
-                }
+                planned_setters.push((monitor, global_setter));
             }
 
         }
     }
 
+    return planned_setters;
+}
+
+pub fn apply_arguments(arguments: Arguments) -> Result<(), String> {
+    // get devices
+    let backend: Box<dyn BrightnessBackend> = arguments.backend.create(arguments.flag_raw)?;
+    let devices: HashMap<usize, Monitor> = get_all_devices(backend.as_ref(), &arguments)?;
+
+    let planned_setters: Vec<(&Monitor, &BArg)> = plan_setters(&devices, &arguments.bargs.setters);
+    apply_planned_setters(planned_setters, &arguments);
+
     debug!("Successfully applied all brightness arguments");
 
     // apply getters
     if let Some(getters) = arguments.bargs.getters {
         match getters {
-            Getter::Global => devices.values().for_each(|monitor| monitor.print(&arguments.progressbar_info)), // print all monitors
+            Getter::Global => { // print all monitors, sorted so records come in the same order every time
+                let mut indices: Vec<&usize> = devices.keys().collect();
+                indices.sort();
+                indices.into_iter().for_each(|index| devices[index].print(&arguments.progressbar_info, arguments.template.as_ref()));
+            },
             Getter::Many(indexed_getters) => {
+                let mut printed_indices: HashSet<usize> = HashSet::<usize>::new();
                 for scope in indexed_getters {
                     match scope {
                         BScope::Global => {
@@ -258,10 +882,12 @@ pub fn apply_arguments(arguments: Arguments) -> Result<(), String> {
                             error!("Unexpected `global getter` inside `indexed getters` array");
                         },
 
-                        BScope::Index(index) => {
-                            match devices.get(&index) {
-                                None => warn!("Monitor with index `{}` not found. Check the `zero` flag if you think this is an error", index),
-                                Some(monitor) => monitor.print(&arguments.progressbar_info)
+                        scope => {
+                            // overlapping scopes print every monitor once
+                            for index in resolve_scope(&devices, &scope) {
+                                if printed_indices.insert(index) {
+                                    devices[&index].print(&arguments.progressbar_info, arguments.template.as_ref());
+                                }
                             }
                         }
                     }
@@ -272,3 +898,238 @@ pub fn apply_arguments(arguments: Arguments) -> Result<(), String> {
 
     return Ok(());
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::backend::FakeBackend;
+
+    // fake monitors from JSON descriptions, the configuration file `config` and the command line `monb <args>`
+    fn configured_monitors(monitors: &[String], config: &str, args: &[&str]) -> (TempDir, Arguments, HashMap<usize, Monitor>) {
+        let directory: TempDir = tempfile::tempdir().unwrap();
+        let description: PathBuf = directory.path().join("monitors.json");
+        let config_path: PathBuf = directory.path().join("config.toml");
+        fs::write(&description, format!(r#"{{ "monitors": [{}] }}"#, monitors.join(", "))).unwrap();
+        fs::write(&config_path, config).unwrap();
+
+        let arguments: Arguments = crate::arguments(&[&["monb", "-c", config_path.to_str().unwrap()], args].concat());
+        let backend: FakeBackend = FakeBackend::from_file(description).unwrap();
+        let devices: HashMap<usize, Monitor> = get_all_devices(&backend, &arguments).unwrap();
+        return (directory, arguments, devices);
+    }
+
+    // fake monitors with the given brightness values (on a 0..=100 scale) and an empty configuration
+    fn fake_monitors(brightness: &[u32], args: &[&str]) -> (TempDir, Arguments, HashMap<usize, Monitor>) {
+        let monitors: Vec<String> = brightness.iter().map(|b| format!(r#"{{ "name": "Fake", "brightness": {} }}"#, b)).collect();
+        return configured_monitors(&monitors, "", args);
+    }
+
+    // brightness each monitor would be set to, by monitor index
+    fn planned_values(bargs: &[&str]) -> Vec<(usize, usize)> {
+        let (_directory, arguments, devices) = fake_monitors(&[40; 4], bargs);
+
+        let mut values: Vec<(usize, usize)> = plan_setters(&devices, &arguments.bargs.setters).into_iter()
+            .map(|(monitor, barg)| (monitor.index, barg.value.brightness.unwrap()))
+            .collect();
+        values.sort();
+        return values;
+    }
+
+    // brightness of a single monitor after `monb <args>`
+    fn changed(brightness: u32, args: &[&str]) -> u32 {
+        let (_directory, arguments, devices) = fake_monitors(&[brightness], args);
+        apply_setter_barg(&devices[&1], &arguments.bargs.setters[0], None).unwrap();
+        return devices[&1].get().unwrap();
+    }
+
+    #[test]
+    fn the_most_specific_scope_wins() {
+        let expected: Vec<(usize, usize)> = vec![(1, 50), (2, 10), (3, 50), (4, 90)];
+
+        assert_eq!(planned_values(&["90", "1-3:50", "2:10"]), expected);
+        assert_eq!(planned_values(&["2:10", "1-3:50", "*:90"]), expected);
+    }
+
+    #[test]
+    fn exclusions_lose_to_ranges_and_win_over_the_global_setter() {
+        assert_eq!(planned_values(&["90", "^4:30", "3-4:50"]), vec![(1, 30), (2, 30), (3, 50), (4, 50)]);
+        assert_eq!(planned_values(&["90", "^1,2:30"]), vec![(1, 90), (2, 90), (3, 30), (4, 30)]);
+    }
+
+    #[test]
+    fn relative_steps_round_to_the_nearest_unit() {
+        assert_eq!(relative_step(33, 10), 3);
+        assert_eq!(relative_step(35, 10), 4);
+        assert_eq!(relative_step(19393, 10), 1939);
+        assert_eq!(relative_step(u32::MAX, 100), u32::MAX);
+    }
+
+    #[test]
+    fn relative_steps_move_at_least_one_unit() {
+        assert_eq!(relative_step(0, 10), 1);
+        assert_eq!(relative_step(4, 10), 1);
+        assert_eq!(relative_step(40, 0), 0);
+    }
+
+    #[test]
+    fn multipliers_round_to_the_nearest_value() {
+        assert_eq!(changed(33, &["1:*1.5"]), 50);
+        assert_eq!(changed(33, &["1:*0.5"]), 17);
+        assert_eq!(changed(33, &["1:*0"]), 0);
+    }
+
+    #[test]
+    fn multipliers_stop_at_the_maximum() {
+        assert_eq!(changed(40, &["1:*3"]), 100);
+        assert_eq!(changed(40, &["--raw", "1:*3"]), 100);
+        assert_eq!(changed(40, &["--raw", "1:*1000000000000"]), 100);
+    }
+
+    #[test]
+    fn relative_percentages_leave_and_reach_zero() {
+        assert_eq!(changed(0, &["1:+10%"]), 1);
+        assert_eq!(changed(3, &["1:-10%"]), 2);
+        assert_eq!(changed(1, &["1:-10%"]), 0);
+        assert_eq!(changed(0, &["1:-10%"]), 0);
+    }
+
+    #[test]
+    fn relative_percentages_scale_the_current_value() {
+        assert_eq!(changed(50, &["1:+10%"]), 55);
+        assert_eq!(changed(50, &["1:-10%"]), 45);
+        assert_eq!(changed(95, &["1:+10%"]), 100);
+    }
+
+    #[test]
+    fn step_tolerance_is_two_percent_of_the_range() {
+        assert_eq!(step_tolerance(100), 2);
+        assert_eq!(step_tolerance(19393), 388);
+        assert_eq!(step_tolerance(10), 1);
+        assert_eq!(step_tolerance(0), 1);
+    }
+
+    #[test]
+    fn toggles_match_values_within_tolerance_of_the_first_step() {
+        for value in [18, 20, 22] {
+            assert_eq!(toggle_step(value, &[20, 80], 2), 80, "`{}`", value);
+        }
+        for value in [17, 23, 78, 80, 82] {
+            assert_eq!(toggle_step(value, &[20, 80], 2), 20, "`{}`", value);
+        }
+    }
+
+    #[test]
+    fn toggles_between_codes_match_exactly() {
+        assert_eq!(toggle_step(15, &[15, 17], 0), 17);
+        assert_eq!(toggle_step(17, &[15, 17], 0), 15);
+        assert_eq!(toggle_step(14, &[15, 17], 0), 15);
+        assert_eq!(toggle_step(16, &[15, 17], 0), 15);
+    }
+
+    #[test]
+    fn cycles_wrap_around_past_the_last_step() {
+        assert_eq!(cycle_step(80, &[20, 50, 80], 2), 20);
+        assert_eq!(cycle_step(82, &[20, 50, 80], 2), 20);
+        assert_eq!(cycle_step(78, &[20, 50, 80], 2), 20);
+        assert_eq!(cycle_step(20, &[20, 50, 80], 2), 50);
+        assert_eq!(cycle_step(18, &[20, 50, 80], 2), 50);
+        assert_eq!(cycle_step(52, &[20, 50, 80], 2), 80);
+    }
+
+    #[test]
+    fn cycles_without_a_match_go_to_the_next_step_above() {
+        assert_eq!(cycle_step(77, &[20, 50, 80], 2), 80);
+        assert_eq!(cycle_step(30, &[20, 50, 80], 2), 50);
+        assert_eq!(cycle_step(83, &[20, 50, 80], 2), 20);
+        assert_eq!(cycle_step(0, &[20, 50, 80], 2), 20);
+    }
+
+    #[test]
+    fn cycles_between_codes_match_exactly() {
+        assert_eq!(cycle_step(18, &[15, 17, 18], 0), 15);
+        assert_eq!(cycle_step(17, &[15, 17, 18], 0), 18);
+        assert_eq!(cycle_step(16, &[15, 17, 18], 0), 17);
+        assert_eq!(cycle_step(19, &[15, 17, 18], 0), 15);
+    }
+
+    #[test]
+    fn cycles_pick_the_nearest_of_overlapping_steps() {
+        assert_eq!(cycle_step(10, &[10, 12, 50], 2), 12);
+        assert_eq!(cycle_step(12, &[10, 12, 50], 2), 50);
+
+        // equally near steps, the first one wins
+        assert_eq!(cycle_step(11, &[10, 12, 50], 2), 12);
+    }
+
+    #[test]
+    fn toggles_and_cycles_go_through_the_monitor() {
+        assert_eq!(changed(79, &["1:toggle(20,80)"]), 20);
+        assert_eq!(changed(21, &["1:toggle(20,80)"]), 80);
+        assert_eq!(changed(81, &["1:cycle(20,50,80)"]), 20);
+    }
+
+    #[test]
+    fn monitor_limits_override_global_limits() {
+        let monitors: Vec<String> = vec![
+            r#"{ "name": "Limited", "brightness": 40, "connector": "DP-2" }"#.into(),
+            r#"{ "name": "Global", "brightness": 40 }"#.into()
+        ];
+        let config: &str = "[limits.\"*\"]\nmin = 10\nmax = 90\n\n[limits.\"DP-2\"]\nmax = 50\n";
+
+        let (_directory, arguments, devices) = configured_monitors(&monitors, config, &["100"]);
+        assert_eq!(devices[&1].limits, Limits { min: Some(10), max: Some(50) });
+        assert_eq!(devices[&2].limits, Limits { min: Some(10), max: Some(90) });
+
+        for monitor in [&devices[&1], &devices[&2]] {
+            apply_setter_barg(monitor, &arguments.bargs.setters[0], None).unwrap();
+        }
+        assert_eq!(devices[&1].get(), Ok(50));
+        assert_eq!(devices[&2].get(), Ok(90));
+    }
+
+    // value `monb -p 1:` prints after `monb <args>`
+    fn perceptual_reading(brightness: u32, args: &[&str]) -> u32 {
+        let (_directory, arguments, devices) = fake_monitors(&[brightness], &[&["-p"], args].concat());
+        apply_setter_barg(&devices[&1], &arguments.bargs.setters[0], None).unwrap();
+        return devices[&1].percentage(devices[&1].get().unwrap(), devices[&1].max());
+    }
+
+    #[test]
+    fn perceptual_values_are_set_on_the_scale_getters_show() {
+        assert_eq!(changed(40, &["-p", "1:50"]), perceptual::from_perceptual(50, 100));
+        assert_eq!(perceptual_reading(40, &["1:0"]), 0);
+        assert_eq!(perceptual_reading(40, &["1:100"]), 100);
+
+        // a 0..=100 device can't show every perceptual value, the nearest one is set
+        for lightness in (10..=100).step_by(10) {
+            let reading: u32 = perceptual_reading(40, &[&format!("1:{}", lightness)]);
+            assert!(reading.abs_diff(lightness) <= 1, "`{}` reads back as `{}`", lightness, reading);
+        }
+    }
+
+    #[test]
+    fn perceptual_conditions_and_values_share_a_scale() {
+        let dark: u32 = perceptual::from_perceptual(40, 100);
+
+        assert_eq!(changed(dark, &["-p", "1:60 if <50"]), perceptual::from_perceptual(60, 100));
+        assert_eq!(changed(dark, &["-p", "1:60 if >50"]), dark);
+    }
+
+    #[test]
+    fn perceptual_toggles_use_perceptual_steps() {
+        let (low, high): (u32, u32) = (perceptual::from_perceptual(20, 100), perceptual::from_perceptual(80, 100));
+
+        assert_eq!(changed(low, &["-p", "1:toggle(20,80)"]), high);
+        assert_eq!(changed(high, &["-p", "1:toggle(20,80)"]), low);
+    }
+
+    #[test]
+    fn exact_values_without_perceptual_are_device_values() {
+        assert_eq!(changed(40, &["1:50"]), 50);
+    }
+}
@@ -0,0 +1,236 @@
+use std::io::Write;
+use std::sync::Once;
+
+use log::{debug, error};
+use serde::Serialize;
+use strum::{Display, EnumIter, IntoStaticStr};
+
+/// Format of getter and setter results on stdout, selected with `--output`.
+/// Structured formats print one record per monitor and move logs to stderr.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, IntoStaticStr, Display)]
+pub enum OutputFormat {
+    #[default]
+    #[strum(serialize = "text")]    Text,
+
+    #[strum(serialize = "json")]    Json,
+    #[strum(serialize = "tsv")]     Tsv
+}
+
+impl TryFrom<Option<&String>> for OutputFormat {
+    type Error = String;
+
+    fn try_from(value: Option<&String>) -> Result<Self, Self::Error> {
+        let potential_format: &String = match value {
+            None => return Ok(Self::default()),
+            Some(v) => v
+        };
+
+        debug!("Parsing output format: `{}`", potential_format);
+
+        match potential_format.trim().to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "tsv" => Ok(Self::Tsv),
+            _ => Err(format!("Invalid output format: `{}`", potential_format))
+        }
+    }
+}
+
+impl OutputFormat {
+    pub fn is_structured(&self) -> bool {
+        *self != Self::Text
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(IntoStaticStr)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Unchanged,
+    Skipped,
+    Interrupted,
+    Error
+}
+
+/// Result of a getter or a setter for one monitor. Values are on the `0..=max` scale of the monitor,
+/// limits are `None` if the monitor doesn't have them.
+#[derive(Debug, Serialize)]
+pub struct Record<'a> {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub index: usize,
+    pub id: &'a str,
+    pub name: &'a str,
+    pub backend: &'static str,
+    pub feature: &'static str,
+
+    // value before the setter, `None` for getters
+    pub previous: Option<u32>,
+    pub value: Option<u32>,
+    pub max: u32,
+    pub limit_min: Option<u32>,
+    pub limit_max: Option<u32>,
+
+    pub status: Status,
+    pub error: Option<String>
+}
+
+const TSV_HEADER: [&'static str; 13] = [
+    "type", "index", "id", "name", "backend", "feature", "previous", "value", "max", "limit_min", "limit_max", "status", "error"
+];
+
+// the header is printed before the first record only
+static TSV_HEADER_PRINTED: Once = Once::new();
+
+// tabs and line breaks would break the columns
+fn tsv_field(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+fn tsv_number(number: Option<u32>) -> String {
+    number.map(|n| n.to_string()).unwrap_or_default()
+}
+
+impl Record<'_> {
+    // the record as one line of `format`, `None` for text output
+    fn line(&self, format: OutputFormat) -> Option<String> {
+        match format {
+            OutputFormat::Text => None,
+            OutputFormat::Json => match serde_json::to_string(self) {
+                Ok(json) => Some(json),
+                Err(err) => {
+                    error!("Failed to serialize output record ({})", err);
+                    None
+                }
+            },
+            OutputFormat::Tsv => {
+                let status: &'static str = self.status.into();
+                Some([
+                    self.kind.to_string(),
+                    self.index.to_string(),
+                    tsv_field(self.id),
+                    tsv_field(self.name),
+                    self.backend.to_string(),
+                    self.feature.to_string(),
+                    tsv_number(self.previous),
+                    tsv_number(self.value),
+                    self.max.to_string(),
+                    tsv_number(self.limit_min),
+                    tsv_number(self.limit_max),
+                    status.to_string(),
+                    tsv_field(self.error.as_deref().unwrap_or_default())
+                ].join("\t"))
+            }
+        }
+    }
+
+    // `header` makes sure the TSV header is written before the first record only
+    fn write(&self, output: &mut impl Write, format: OutputFormat, header: &Once) -> std::io::Result<()> {
+        let line: String = match self.line(format) {
+            Some(l) => l,
+            None => return Ok(())
+        };
+
+        let mut header_result: std::io::Result<()> = Ok(());
+        if format == OutputFormat::Tsv {
+            header.call_once(|| header_result = writeln!(output, "{}", TSV_HEADER.join("\t")));
+        }
+        header_result?;
+
+        return writeln!(output, "{}", line);
+    }
+
+    pub fn print(&self, format: OutputFormat) {
+        // setters run in parallel during fades, records are written whole
+        let mut stdout = std::io::stdout().lock();
+        if let Err(err) = self.write(&mut stdout, format, &TSV_HEADER_PRINTED) {
+            debug!("Failed to write output record ({})", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn record(name: &str, error: Option<String>) -> Record<'_> {
+        Record {
+            kind: "set",
+            index: 1,
+            id: "DP-1",
+            name,
+            backend: "fake",
+            feature: "brightness",
+            previous: Some(40),
+            value: Some(60),
+            max: 100,
+            limit_min: None,
+            limit_max: Some(90),
+            status: Status::Ok,
+            error
+        }
+    }
+
+    fn written(records: &[Record], format: OutputFormat) -> String {
+        let header: Once = Once::new();
+        let mut output: Vec<u8> = Vec::new();
+        for record in records {
+            record.write(&mut output, format, &header).unwrap();
+        }
+        return String::from_utf8(output).unwrap();
+    }
+
+    #[test]
+    fn json_records_have_the_tsv_columns() {
+        let json: serde_json::Value = serde_json::from_str(&record("Monitor #1", None).line(OutputFormat::Json).unwrap()).unwrap();
+
+        let fields: BTreeSet<&str> = json.as_object().unwrap().keys().map(|key| key.as_str()).collect();
+        assert_eq!(fields, TSV_HEADER.into_iter().collect::<BTreeSet<&str>>());
+    }
+
+    #[test]
+    fn json_records_keep_missing_values_as_null() {
+        let json: String = record("Monitor #1", None).line(OutputFormat::Json).unwrap();
+
+        assert_eq!(json, concat!(
+            r#"{"type":"set","index":1,"id":"DP-1","name":"Monitor #1","backend":"fake","feature":"brightness","#,
+            r#""previous":40,"value":60,"max":100,"limit_min":null,"limit_max":90,"status":"ok","error":null}"#
+        ));
+    }
+
+    #[test]
+    fn tsv_fields_replace_tabs_and_line_breaks() {
+        let line: String = record("Dell\tU2415\r\nrev A", Some("bus\terror\n".into())).line(OutputFormat::Tsv).unwrap();
+
+        assert_eq!(line, "set\t1\tDP-1\tDell U2415  rev A\tfake\tbrightness\t40\t60\t100\t\t90\tok\tbus error ");
+        assert_eq!(line.split('\t').count(), TSV_HEADER.len());
+    }
+
+    #[test]
+    fn tsv_header_is_written_once() {
+        let output: String = written(&[record("Monitor #1", None), record("Monitor #2", None)], OutputFormat::Tsv);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], TSV_HEADER.join("\t"));
+        assert!(lines[1..].iter().all(|line| line.starts_with("set\t")), "{}", output);
+    }
+
+    #[test]
+    fn json_records_have_no_header() {
+        let output: String = written(&[record("Monitor #1", None), record("Monitor #2", None)], OutputFormat::Json);
+
+        assert_eq!(output.lines().count(), 2);
+        assert!(output.lines().all(|line| line.starts_with('{')), "{}", output);
+    }
+
+    #[test]
+    fn text_output_writes_nothing() {
+        assert_eq!(written(&[record("Monitor #1", None)], OutputFormat::Text), "");
+    }
+}
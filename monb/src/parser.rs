@@ -2,27 +2,35 @@ use std::collections::HashSet;
 use std::num::IntErrorKind;
 use std::str::FromStr;
 
-use clap::parser::ValuesRef;
 use regex::Captures;
 
 use log::{debug, warn};
 
-use crate::cli::regexes::{BARG_REGEX, SCOPE_GROUP, VALUE_GROUP, ACTION_GROUP, BRIGHTNESS_GROUP, DENOMINATOR_GROUP, PERCENTAGE_GROUP, MAX_GROUP, MIN_GROUP};
-use crate::cli::params::{BArg, BScope, BValue, BAction, BArgs, Getter};
-
-pub fn parse_bargs(possible_raw_bargs: Option<ValuesRef<String>>) -> Result<BArgs, String> {
-    let raw_bargs: ValuesRef<String> = match possible_raw_bargs {
+use crate::cli::regexes::{BARG_REGEX, SCOPE_GROUP, VALUE_GROUP, ACTION_GROUP, BRIGHTNESS_GROUP, DENOMINATOR_GROUP, PERCENTAGE_GROUP, MAX_GROUP, MIN_GROUP, MULTIPLY_GROUP, FACTOR_GROUP, STEPS_ACTION_GROUP, STEPS_GROUP, COMPARISON_GROUP, CONDITION_GROUP};
+use crate::cli::params::{BArg, BScope, BValue, BAction, BArgs, Getter, BComparison, BCondition};
+use crate::config::Config;
+use crate::feature::Feature;
+
+// `value_max` is the largest brightness value an argument can hold, 100 for percentages
+// `feature` rejects relative values for features that are codes (input source, power mode)
+// identifiers naming an alias from `config` are replaced with the aliased scope
+// arguments come from the command line or from a scene in the configuration file
+pub fn parse_bargs<'a, I>(possible_raw_bargs: Option<I>, value_max: usize, feature: Feature, config: &Config) -> Result<BArgs, String>
+where
+    I: IntoIterator<Item = &'a String>
+{
+    let raw_bargs: I = match possible_raw_bargs {
         Some(rb) => rb,
         None => return Ok(BArgs::default())
     };
 
     let mut parsed_bargs: Vec<BArg> = Vec::<BArg>::new();
 
-    for potential_barg in raw_bargs {
+    for potential_barg in &merge_conditions(raw_bargs)? {
         debug!("Parsing brightness argument: `{}`", potential_barg);
 
         let result: BArg = match BARG_REGEX.captures(potential_barg) {
-            Some(c) => parse_barg_from_regex_captures(c)?,
+            Some(c) => parse_barg_from_regex_captures(c, value_max, feature, config)?,
             None => return Err(format!("Input `{}` is not a valid brightness argument", potential_barg))
         };
         debug!("Brightness argument `{}` parsed successfully into `{}`", potential_barg, result.to_string());
@@ -30,11 +38,11 @@ pub fn parse_bargs(possible_raw_bargs: Option<ValuesRef<String>>) -> Result<BArg
     }
 
     let mut global_get: bool = false; // used for merging indexed gets if global get is present; 1: 3: *: 2: --> *:
-    let mut getter_bargs_scopes: HashSet<usize> = HashSet::<usize>::new();
+    let mut getter_bargs_scopes: HashSet<BScope> = HashSet::<BScope>::new();
     let mut getter_bargs: Vec<BScope> = Vec::<BScope>::new();
 
     let mut global_set: Option<BArg> = None; // used to put global barg at the end of the array
-    let mut setter_bargs_scopes: HashSet<usize> = HashSet::<usize>::new();
+    let mut setter_bargs_scopes: HashSet<BScope> = HashSet::<BScope>::new();
     let mut setter_bargs: Vec<BArg> = Vec::<BArg>::new();
 
     for barg in parsed_bargs {
@@ -47,9 +55,9 @@ pub fn parse_bargs(possible_raw_bargs: Option<ValuesRef<String>>) -> Result<BArg
 
             match barg.scope {
                 BScope::Global => { global_get = true; },
-                BScope::Index(index) => {
-                    if !getter_bargs_scopes.contains(&index) {
-                        getter_bargs_scopes.insert(index);
+                _ => {
+                    if !getter_bargs_scopes.contains(&barg.scope) {
+                        getter_bargs_scopes.insert(barg.scope.clone());
                         getter_bargs.push(barg.scope);
                     } else {
                         debug!("Duplicate `get` brightness argument for scope: `{}`", barg.scope.to_string())
@@ -67,18 +75,21 @@ pub fn parse_bargs(possible_raw_bargs: Option<ValuesRef<String>>) -> Result<BArg
                     }
                 },
 
-                BScope::Index(index) => {
-                    if !setter_bargs_scopes.contains(&index) {
-                        setter_bargs_scopes.insert(index);
+                _ => {
+                    if !setter_bargs_scopes.contains(&barg.scope) {
+                        setter_bargs_scopes.insert(barg.scope.clone());
                         setter_bargs.push(barg);
                     } else {
-                        debug!("Duplicate brightness argument for scope: `{}`", index);
+                        debug!("Duplicate brightness argument for scope: `{}`", barg.scope.to_string());
                     }
                 }
             }
         }
     }
 
+    // more specific scopes first, the sort is stable so the first argument wins between equally specific scopes
+    setter_bargs.sort_by_key(|barg| barg.scope.precedence());
+
     if let Some(global_setter) = global_set {
         setter_bargs.push(global_setter);
     }
@@ -99,7 +110,43 @@ pub fn parse_bargs(possible_raw_bargs: Option<ValuesRef<String>>) -> Result<BArg
     });
 }
 
-fn parse_brightness_value_from_str(brightness_raw: &str, is_fraction: bool) -> Result<usize, String> {
+// conditions can be passed as separate arguments, `1:50 if '<30'` is the same as `'1:50 if <30'`
+fn merge_conditions<'a>(raw_bargs: impl IntoIterator<Item = &'a String>) -> Result<Vec<String>, String> {
+    let mut merged: Vec<String> = Vec::<String>::new();
+    let mut raw_bargs = raw_bargs.into_iter();
+
+    while let Some(raw_barg) = raw_bargs.next() {
+        let condition: &str = match raw_barg.trim().get(..2) {
+            Some(keyword) if keyword.eq_ignore_ascii_case("if") => raw_barg.trim()[2..].trim_start(),
+            _ => {
+                merged.push(raw_barg.clone());
+                continue;
+            }
+        };
+
+        // `if` on its own takes the next argument, `if<30` and `if <30` already carry the comparison
+        let condition: String = match condition.is_empty() {
+            true => match raw_bargs.next() {
+                Some(next) => next.trim().to_string(),
+                None => return Err("Condition `if` is missing the comparison, e.g. `if <30`".into())
+            },
+            false if condition.starts_with(['<', '>', '=']) => condition.to_string(),
+            false => {
+                merged.push(raw_barg.clone());
+                continue;
+            }
+        };
+
+        match merged.last_mut() {
+            Some(previous) => previous.push_str(&format!(" if {}", condition)),
+            None => return Err(format!("Condition `if {}` has no brightness argument before it", condition))
+        }
+    }
+
+    return Ok(merged);
+}
+
+fn parse_brightness_value_from_str(brightness_raw: &str, is_fraction: bool, value_max: usize) -> Result<usize, String> {
     let mut brightness: usize = match brightness_raw.parse::<usize>() {
         Ok(bv) => bv,
         Err(err) => match err.kind() {
@@ -108,9 +155,9 @@ fn parse_brightness_value_from_str(brightness_raw: &str, is_fraction: bool) -> R
         }
     };
 
-    if brightness > 100 && !is_fraction {
-        debug!("Brightness value `{}` is bigger than 100, clamping to 100", brightness_raw);
-        brightness = 100;
+    if brightness > value_max && !is_fraction {
+        debug!("Brightness value `{}` is bigger than {}, clamping to {}", brightness_raw, value_max, value_max);
+        brightness = value_max;
     }
 
     return Ok(brightness);
@@ -128,7 +175,14 @@ fn parse_denominator_value_from_str(denominator_str: &str) -> Result<Option<usiz
     return Ok(Some(denominator));
 }
 
-fn calculate_final_value(brightness_value: usize, denominator: Option<usize>, percentage: bool) -> Result<usize, String> {
+fn parse_factor_from_str(factor_str: &str) -> Result<f64, String> {
+    match factor_str.parse::<f64>() {
+        Ok(factor) if factor.is_finite() => Ok(factor),
+        _ => Err(format!("Multiplier `{}` is not valid", factor_str))
+    }
+}
+
+fn calculate_final_value(brightness_value: usize, denominator: Option<usize>, percentage: bool, value_max: usize) -> Result<usize, String> {
     if let Some(denominator_value) = denominator {
 
         if denominator_value == 0 {
@@ -141,9 +195,9 @@ fn calculate_final_value(brightness_value: usize, denominator: Option<usize>, pe
             brightness_value / denominator_value
         };
 
-        if final_value > 100 {
-            debug!("Brightness value `{}` is bigger than 100, clamping to 100", final_value);
-            final_value = 100;
+        if final_value > value_max {
+            debug!("Brightness value `{}` is bigger than {}, clamping to {}", final_value, value_max, value_max);
+            final_value = value_max;
         }
 
         return Ok(final_value);
@@ -153,23 +207,82 @@ fn calculate_final_value(brightness_value: usize, denominator: Option<usize>, pe
     return Ok(brightness_value);
 }
 
-fn parse_barg_from_regex_captures(captures: Captures) -> Result<BArg, String> {
+// `if <30`, compared against the current value, in the same units as the brightness value
+fn parse_condition_from_regex_captures(captures: &Captures, value_max: usize) -> Result<Option<BCondition>, String> {
+    let comparison: BComparison = match captures.name(COMPARISON_GROUP) {
+        None => return Ok(None),
+        Some(c) => BComparison::from_str(c.as_str())?
+    };
+
+    let brightness: usize = parse_brightness_value_from_str(&captures[CONDITION_GROUP], false, value_max)?;
+
+    return Ok(Some(BCondition { comparison, brightness }));
+}
+
+fn parse_barg_from_regex_captures(captures: Captures, value_max: usize, feature: Feature, config: &Config) -> Result<BArg, String> {
     let scope: BScope = match captures.name(SCOPE_GROUP) {
         None => BScope::Global,
-        Some(c) => BScope::from_str(c.as_str())?
+        Some(c) => BScope::from_str(c.as_str())?.expand_aliases(config)
     };
 
+    let condition: Option<BCondition> = parse_condition_from_regex_captures(&captures, value_max)?;
+
     if let None = captures.name(VALUE_GROUP) {
+        if condition.is_some() {
+            return Err(format!("Getter for scope `{}` can't have a condition, conditions only apply to setters", scope.to_string()));
+        }
+
+        return Ok(BArg::new(scope, BValue::get()));
+    };
+
+    // `toggle(20,80)` or `cycle(10,40,70,100)`
+    if let Some(steps_action) = captures.name(STEPS_ACTION_GROUP) {
+        let is_toggle: bool = steps_action.as_str().eq_ignore_ascii_case("toggle");
+        let steps: Vec<usize> = captures[STEPS_GROUP].split(',')
+            .map(|step| parse_brightness_value_from_str(step.trim(), false, value_max))
+            .collect::<Result<Vec<usize>, String>>()?;
+
+        if is_toggle && steps.len() != 2 {
+            return Err(format!("Toggle needs exactly 2 values, got {}", steps.len()));
+        }
+        if steps.len() < 2 {
+            return Err(format!("Cycle needs at least 2 values, got {}", steps.len()));
+        }
+
         return Ok(BArg {
             scope,
-            value: BValue {
-                action: BAction::Get,
-                brightness: None
-            }
-        })
-    };
+            value: BValue::steps(crate::ternary_operator!(is_toggle, BAction::Toggle, BAction::Cycle), steps),
+            condition
+        });
+    }
+
+    // input source and power mode are codes, only exact values and `toggle`/`cycle` between them make sense
+    if !feature.is_continuous() {
+        let is_exact: bool = captures.name(BRIGHTNESS_GROUP).is_some()
+            && captures.name(DENOMINATOR_GROUP).is_none()
+            && captures.name(PERCENTAGE_GROUP).is_none()
+            && matches!(captures.name(ACTION_GROUP).map(|action| BAction::from_str(action.as_str())), None | Some(Ok(BAction::Set)));
+
+        if !is_exact {
+            return Err(format!(
+                "Value `{}` for scope `{}` can't be used with {}, codes only take exact values, `toggle` and `cycle`",
+                &captures[VALUE_GROUP], scope.to_string(), feature.description()
+            ));
+        }
+    }
 
-    let action: BAction = match captures.name(ACTION_GROUP) {
+    // multiplier, `x1.5` or `*0.5`
+    if captures.name(MULTIPLY_GROUP).is_some() {
+        let factor: f64 = parse_factor_from_str(&captures[FACTOR_GROUP])?;
+
+        return Ok(BArg {
+            scope,
+            value: BValue::multiply(factor),
+            condition
+        });
+    }
+
+    let mut action: BAction = match captures.name(ACTION_GROUP) {
         None => BAction::Set,
         Some(c) => BAction::from_str(c.as_str())?
     };
@@ -184,41 +297,43 @@ fn parse_barg_from_regex_captures(captures: Captures) -> Result<BArg, String> {
         };
         let is_fraction: bool = denominator.is_some();
 
-        // main brightness value
-        let brightness_value: usize = parse_brightness_value_from_str(brightness_value_raw.as_str(), is_fraction)?;
+        // `+10%` and `-20%` are relative to the current value, ratios like `+1/4%` are still absolute points
+        let is_relative: bool = !is_fraction && captures.name(PERCENTAGE_GROUP).is_some() && action != BAction::Set;
+        if is_relative {
+            action = crate::ternary_operator!(action == BAction::Inc, BAction::IncPercent, BAction::DecPercent);
+        }
+
+        // main brightness value, relative percentages can go above the maximum (`+200%` triples the value)
+        let brightness_value: usize = parse_brightness_value_from_str(brightness_value_raw.as_str(), is_fraction || is_relative, value_max)?;
 
-        let final_value: usize = calculate_final_value(brightness_value, denominator, captures.name(PERCENTAGE_GROUP).is_some())?;
+        let final_value: usize = calculate_final_value(brightness_value, denominator, captures.name(PERCENTAGE_GROUP).is_some(), value_max)?;
 
         return Ok(BArg {
             scope,
-            value: BValue {
-                action,
-                brightness: Some(final_value)
-            }
+            value: BValue::new(action, final_value),
+            condition
         });
     }
 
     // +-----+--------+----------+
     // |     | Min    | Max      |
     // +-----+--------+----------+
-    // | Inc | Inc(1) | Set(100) |
+    // | Inc | Inc(1) | Set(max) |
     // | Dec | Dec(1) | Set(0)   |
-    // | Set | Set(0) | Set(100) |
+    // | Set | Set(0) | Set(max) |
     // +-----+--------+----------+
 
     // max brightness value
     if captures.name(MAX_GROUP).is_some() {
         let value: usize = match action {
             BAction::Dec => 0,
-            _ => 100
+            _ => value_max
         };
 
         return Ok(BArg {
             scope,
-            value: BValue {
-                action: BAction::Set,
-                brightness: Some(value)
-            }
+            value: BValue::new(BAction::Set, value),
+            condition
         })
     };
 
@@ -231,10 +346,8 @@ fn parse_barg_from_regex_captures(captures: Captures) -> Result<BArg, String> {
 
         return Ok(BArg {
             scope,
-            value: BValue {
-                action: BAction::Set,
-                brightness: Some(value)
-            }
+            value: BValue::new(BAction::Set, value),
+            condition
         })
     };
 
@@ -242,3 +355,85 @@ fn parse_barg_from_regex_captures(captures: Captures) -> Result<BArg, String> {
     debug!("Should not happen!");
     return Err("Unrecognized brightness argument".into());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(bargs: &[&str], feature: Feature) -> Result<BArgs, String> {
+        let bargs: Vec<String> = bargs.iter().map(|barg| barg.to_string()).collect();
+        let value_max: usize = crate::ternary_operator!(feature.is_continuous(), 100, u32::MAX as usize);
+        parse_bargs(Some(&bargs), value_max, feature, &Config::default())
+    }
+
+    #[test]
+    fn codes_take_exact_values_toggle_and_cycle() {
+        for barg in ["15", "1:17", "toggle(15,17)", "cycle(15,17,18)", "1:", "1:15 if =17"] {
+            assert!(parse(&[barg], Feature::InputSource).is_ok(), "`{}`", barg);
+        }
+    }
+
+    #[test]
+    fn codes_reject_bounds_and_relative_values() {
+        for (barg, value) in [("max", "max"), ("1:min", "min"), ("+1", "+1"), ("-1", "-1"), ("50%", "50%"), ("1/2", "1/2"), ("x2", "x2")] {
+            assert_eq!(
+                parse(&[barg], Feature::InputSource).map(|_| ()),
+                Err(format!("Value `{}` for scope `{}` can't be used with input source, codes only take exact values, `toggle` and `cycle`",
+                    value, crate::ternary_operator!(barg.contains(':'), "1", "*")))
+            );
+        }
+    }
+
+    #[test]
+    fn continuous_features_take_bounds_and_relative_values() {
+        for barg in ["max", "1:min", "+1", "-10%", "1/2", "x2"] {
+            assert!(parse(&[barg], Feature::Brightness).is_ok(), "`{}`", barg);
+        }
+    }
+
+    #[test]
+    fn getters_with_conditions_are_rejected() {
+        assert_eq!(
+            parse(&["1: if <30"], Feature::Brightness).map(|_| ()),
+            Err("Getter for scope `1` can't have a condition, conditions only apply to setters".into())
+        );
+    }
+
+    fn scopes(bargs: &[&str]) -> Vec<BScope> {
+        parse(bargs, Feature::Brightness).unwrap().setters.into_iter().map(|barg| barg.scope).collect()
+    }
+
+    #[test]
+    fn ranges_lists_exclusions_and_serials_are_scopes() {
+        let setters: Vec<BArg> = parse(&["1-3:50"], Feature::Brightness).unwrap().setters;
+        assert_eq!(setters, vec![BArg::new(BScope::Range(1, 3), BValue::new(BAction::Set, 50))]);
+
+        assert_eq!(scopes(&["1,3:50"]), vec![BScope::List(vec![BScope::Index(1), BScope::Index(3)])]);
+        assert_eq!(scopes(&["^2:50"]), vec![BScope::Except(vec![BScope::Index(2)])]);
+        assert_eq!(scopes(&["@ABC123:50"]), vec![BScope::Serial("ABC123".into())]);
+    }
+
+    #[test]
+    fn exclusion_prefixes_are_interchangeable() {
+        for barg in ["!2:50", "*-2:50"] {
+            assert_eq!(scopes(&[barg]), scopes(&["^2:50"]), "`{}`", barg);
+        }
+    }
+
+    #[test]
+    fn reversed_ranges_are_rejected() {
+        assert_eq!(parse(&["3-1:50"], Feature::Brightness).map(|_| ()), Err("Range `3-1` is reversed, did you mean `1-3`?".into()));
+    }
+
+    #[test]
+    fn empty_list_items_are_rejected() {
+        assert_eq!(parse(&["1,,2:50"], Feature::Brightness).map(|_| ()), Err("Input `1,,2:50` is not a valid brightness argument".into()));
+    }
+
+    #[test]
+    fn setters_are_ordered_from_the_most_specific_scope() {
+        for bargs in [["90", "1-3:50", "2:10"], ["2:10", "1-3:50", "*:90"], ["*:90", "2:10", "1-3:50"]] {
+            assert_eq!(scopes(&bargs), vec![BScope::Index(2), BScope::Range(1, 3), BScope::Global], "{:?}", bargs);
+        }
+    }
+}
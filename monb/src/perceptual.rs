@@ -0,0 +1,85 @@
+// CIE 1976 lightness (L*), used as the perceptual brightness scale
+//
+// Screen luminance is roughly linear in device units, but the eye is not: going from 1 to 11 looks like a huge jump,
+// while 89 to 99 is hard to notice. L* spreads the steps evenly, a step of 10 looks about the same anywhere on the scale.
+//
+//     L* = 116 * Y^(1/3) - 16    for Y > (6/29)^3
+//     L* = (29/3)^3 * Y          otherwise
+//
+// where Y is the relative luminance (0..=1) and L* is in the 0..=100 range.
+
+const EPSILON: f64 = 216.0 / 24389.0; // (6/29)^3
+const KAPPA: f64 = 24389.0 / 27.0; // (29/3)^3
+
+/// Relative luminance (0..=1) to lightness (0..=100).
+pub fn lightness_from_luminance(luminance: f64) -> f64 {
+    let y: f64 = luminance.clamp(0.0, 1.0);
+    if y > EPSILON {
+        116.0 * y.cbrt() - 16.0
+    } else {
+        KAPPA * y
+    }
+}
+
+/// Lightness (0..=100) to relative luminance (0..=1).
+pub fn luminance_from_lightness(lightness: f64) -> f64 {
+    let l: f64 = lightness.clamp(0.0, 100.0);
+    if l > KAPPA * EPSILON {
+        ((l + 16.0) / 116.0).powi(3)
+    } else {
+        l / KAPPA
+    }
+}
+
+/// Device value (`0..=max`) shown as perceptual percentage.
+pub fn to_perceptual(value: u32, max: u32) -> u32 {
+    if max == 0 {
+        return 0;
+    }
+
+    lightness_from_luminance(value as f64 / max as f64).round() as u32
+}
+
+/// Perceptual percentage shown as device value (`0..=max`).
+pub fn from_perceptual(lightness: u32, max: u32) -> u32 {
+    (luminance_from_lightness(lightness as f64) * max as f64).round() as u32
+}
+
+/// Applies a relative step of `step` perceptual points to the device value `value`.
+/// The result always moves at least one device unit, otherwise small steps could never leave the dark end of the scale.
+pub fn step(value: u32, max: u32, step: i64) -> u32 {
+    if max == 0 || step == 0 {
+        return value.min(max);
+    }
+
+    let lightness: f64 = lightness_from_luminance(value as f64 / max as f64) + step as f64;
+    let desired: u32 = (luminance_from_lightness(lightness) * max as f64).round() as u32;
+
+    if desired == value {
+        return match step > 0 {
+            true => value.saturating_add(1).min(max),
+            false => value.saturating_sub(1)
+        };
+    }
+
+    desired.min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perceptual_percentages_round_trip() {
+        for lightness in 0..=100 {
+            assert_eq!(to_perceptual(from_perceptual(lightness, 19393), 19393), lightness);
+        }
+    }
+
+    #[test]
+    fn perceptual_percentages_end_at_the_device_range() {
+        assert_eq!(from_perceptual(0, 255), 0);
+        assert_eq!(from_perceptual(100, 255), 255);
+        assert_eq!(from_perceptual(50, 100), 18);
+    }
+}
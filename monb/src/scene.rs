@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use log::{debug, error, info, warn};
+use toml_edit::{Array, DocumentMut, Item, Table};
+
+use crate::backend::BrightnessBackend;
+use crate::cli::params::{Arguments, BArgs, BScope, SUBCOMMAND_SCENE_SAVE_NAME};
+use crate::cli::regexes::BARG_REGEX;
+use crate::config::{Config, Scene};
+use crate::monitor::{apply_arguments, get_all_devices, Monitor};
+use crate::parser::parse_bargs;
+
+const SCENES_TABLE: &'static str = "scenes";
+
+fn config_path(config: &Config) -> Result<&PathBuf, String> {
+    config.path.as_ref().ok_or("No configuration directory, use `--config` to set the configuration file".into())
+}
+
+fn available_scenes(config: &Config) -> String {
+    let names: Vec<String> = config.scene_names().iter().map(|name| format!("`{}`", name)).collect();
+    match names.is_empty() {
+        true => "the configuration file has no scenes".into(),
+        false => format!("available scenes: {}", names.join(", "))
+    }
+}
+
+pub fn apply_scene(mut arguments: Arguments, name: Option<&String>) -> Result<(), String> {
+    arguments.bargs = scene_bargs(&arguments, name)?;
+    return apply_arguments(arguments);
+}
+
+// brightness arguments of a scene, invalid ones are reported with their place in the configuration file
+fn scene_bargs(arguments: &Arguments, name: Option<&String>) -> Result<BArgs, String> {
+    let name: &String = match name {
+        Some(n) => n,
+        None => return Err(format!("Scene name is missing, {}", available_scenes(&arguments.config)))
+    };
+
+    let scene: Scene = match arguments.config.scene(name) {
+        Some(s) => s,
+        None => return Err(format!("Scene `{}` not found, {}", name, available_scenes(&arguments.config)))
+    };
+
+    debug!("Applying scene `{}`", scene.name);
+    return parse_bargs(Some(scene.arguments), arguments.value_max, arguments.feature, &arguments.config).map_err(|err| format!(
+        "Invalid scene `{}` in `{}` at line {} ({})",
+        scene.name,
+        config_path(&arguments.config).map(|path| path.display().to_string()).unwrap_or_default(),
+        scene.line,
+        err
+    ));
+}
+
+// most specific identifier that reads back as the same monitor, the index if there isn't any
+fn scene_scope(monitor: &Monitor, index: usize, config: &Config) -> String {
+    let identifier: Option<String> = monitor.keys().into_iter().find(|key| {
+        let is_identifier: bool = matches!(BScope::from_str(key), Ok(BScope::Id(_) | BScope::Serial(_)));
+        is_identifier && config.alias(key).is_none() && BARG_REGEX.is_match(&format!("{}:0", key))
+    });
+
+    match identifier {
+        Some(id) => id,
+        None => {
+            warn!("{} - no identifier can be used in a scene, saving it by index", monitor.name);
+            index.to_string()
+        }
+    }
+}
+
+pub fn save_scene(arguments: Arguments, name: &str) -> Result<(), String> {
+    if name.trim().is_empty() || name.eq_ignore_ascii_case(SUBCOMMAND_SCENE_SAVE_NAME) {
+        return Err(format!("`{}` can't be used as a scene name", name));
+    }
+
+    let backend: Box<dyn BrightnessBackend> = arguments.backend.create(arguments.flag_raw)?;
+    let devices: HashMap<usize, Monitor> = get_all_devices(backend.as_ref(), &arguments)?;
+
+    return write_scene(&devices, &arguments, name);
+}
+
+// saves the current values of `devices` as the scene `name`
+fn write_scene(devices: &HashMap<usize, Monitor>, arguments: &Arguments, name: &str) -> Result<(), String> {
+    let mut indices: Vec<&usize> = devices.keys().collect();
+    indices.sort();
+
+    let mut scene_arguments: Vec<String> = Vec::<String>::new();
+    for index in indices {
+        let monitor: &Monitor = &devices[index];
+        match monitor.get() {
+            // saved on the scale the scene is applied with, perceptual percentages with `--perceptual`
+            Ok(value) => {
+                let value: u32 = crate::ternary_operator!(monitor.perceptual, monitor.percentage(value, monitor.max()), value.min(monitor.max()));
+                scene_arguments.push(format!("{}:{}", scene_scope(monitor, *index, &arguments.config), value));
+            },
+            Err(err) => error!("{} - {}, not saved in the scene", monitor.name, err)
+        };
+    }
+
+    if scene_arguments.is_empty() {
+        return Err(format!("Scene `{}` not saved, no monitor could be read", name));
+    }
+
+    // the scene has to be valid when it's applied later
+    parse_bargs(Some(&scene_arguments), arguments.value_max, arguments.feature, &arguments.config)?;
+
+    let path: &PathBuf = config_path(&arguments.config)?;
+    let content: String = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(format!("Failed to read configuration file `{}` ({})", path.display(), err))
+    };
+
+    // edited in place, comments and formatting of the rest of the file are kept
+    let mut document: DocumentMut = content.parse()
+        .map_err(|err| format!("Invalid configuration file `{}` ({})", path.display(), err))?;
+
+    let scenes: &mut Table = document.entry(SCENES_TABLE)
+        .or_insert(Item::Table(Table::new()))
+        .as_table_mut()
+        .ok_or(format!("`{}` in `{}` is not a table", SCENES_TABLE, path.display()))?;
+
+    // scene names are case-insensitive, an existing scene keeps its name, position and comments
+    let key: String = match scenes.iter().map(|(key, _)| key.to_string()).find(|key| key.eq_ignore_ascii_case(name)) {
+        Some(existing) => {
+            debug!("Replacing scene `{}`", existing);
+            existing
+        },
+        None => name.to_string()
+    };
+    scenes[key.as_str()] = toml_edit::value(scene_arguments.iter().collect::<Array>());
+
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(|err| format!("Failed to create configuration directory `{}` ({})", directory.display(), err))?;
+    }
+    fs::write(path, document.to_string()).map_err(|err| format!("Failed to write configuration file `{}` ({})", path.display(), err))?;
+
+    info!("Scene `{}` saved to `{}` ({})", key, path.display(), scene_arguments.join(" "));
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::backend::FakeBackend;
+
+    const CONFIG: &str = r#"# monb configuration
+[limits."*"]
+max = 90 # keep the eyes safe
+
+[scenes]
+# evening
+night = ["10"]
+"#;
+
+    // fake monitors from `monitors`, and `monb -c <config> <args>`
+    fn setup(directory: &Path, config: &str, monitors: &str, args: &[&str]) -> (Arguments, HashMap<usize, Monitor>) {
+        let description: PathBuf = directory.join("monitors.json");
+        let config_path: PathBuf = directory.join("config.toml");
+        fs::write(&description, format!(r#"{{ "monitors": [{}] }}"#, monitors)).unwrap();
+        fs::write(&config_path, config).unwrap();
+
+        let arguments: Arguments = crate::arguments(&[&["monb", "-c", config_path.to_str().unwrap()], args].concat());
+        let devices: HashMap<usize, Monitor> = get_all_devices(&FakeBackend::from_file(description).unwrap(), &arguments).unwrap();
+        return (arguments, devices);
+    }
+
+    fn saved_scene(config: &str, monitors: &str, name: &str) -> String {
+        let directory = tempfile::tempdir().unwrap();
+        let (arguments, devices) = setup(directory.path(), config, monitors, &[]);
+
+        write_scene(&devices, &arguments, name).unwrap();
+        return fs::read_to_string(directory.path().join("config.toml")).unwrap();
+    }
+
+    #[test]
+    fn saving_keeps_comments_and_formatting() {
+        let saved: String = saved_scene(CONFIG, r#"{ "name": "Laptop", "brightness": 40 }"#, "day");
+
+        assert_eq!(saved, format!("{}day = [\"Laptop:40\"]\n", CONFIG));
+    }
+
+    #[test]
+    fn saving_replaces_existing_scenes_in_place() {
+        let saved: String = saved_scene(CONFIG, r#"{ "name": "Laptop", "brightness": 40 }"#, "NIGHT");
+
+        assert_eq!(saved, CONFIG.replace(r#"night = ["10"]"#, r#"night = ["Laptop:40"]"#));
+    }
+
+    #[test]
+    fn saving_picks_the_most_specific_identifier() {
+        let monitors: &str = r#"
+            { "name": "Serial", "brightness": 10, "connector": "DP-1", "serial": "ABC123" },
+            { "name": "Office Display", "brightness": 20, "connector": "DP-2" },
+            { "name": "Laptop", "brightness": 30, "connector": "eDP-1" },
+            { "name": "Unnamed Display", "brightness": 40 }
+        "#;
+        let saved: String = saved_scene("", monitors, "day");
+
+        assert_eq!(saved, "[scenes]\nday = [\"@ABC123:10\", \"DP-2:20\", \"Laptop:30\", \"4:40\"]\n");
+    }
+
+    #[test]
+    fn saving_skips_identifiers_taken_by_aliases() {
+        let saved: String = saved_scene("[aliases]\nLaptop = \"2\"\n", r#"{ "name": "Laptop", "brightness": 30, "connector": "eDP-1" }"#, "day");
+
+        assert!(saved.ends_with("day = [\"eDP-1:30\"]\n"), "{}", saved);
+    }
+
+    #[test]
+    fn invalid_scenes_are_reported_with_file_and_line() {
+        let directory = tempfile::tempdir().unwrap();
+        let config: String = format!("{}broken = [\"1:abc\"]\n", CONFIG);
+        let (arguments, _) = setup(directory.path(), &config, r#"{ "name": "Laptop", "brightness": 40 }"#, &[]);
+
+        assert_eq!(
+            scene_bargs(&arguments, Some(&"broken".into())).map(|_| ()),
+            Err(format!("Invalid scene `broken` in `{}` at line 8 (Input `1:abc` is not a valid brightness argument)", directory.path().join("config.toml").display()))
+        );
+    }
+
+    #[test]
+    fn unknown_scenes_list_the_available_ones() {
+        let directory = tempfile::tempdir().unwrap();
+        let (arguments, _) = setup(directory.path(), CONFIG, r#"{ "name": "Laptop", "brightness": 40 }"#, &[]);
+
+        assert_eq!(scene_bargs(&arguments, Some(&"day".into())).map(|_| ()), Err("Scene `day` not found, available scenes: `night`".into()));
+    }
+}
@@ -0,0 +1,17 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::warn;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Turns Ctrl-C into a flag instead of killing the process, so fades stop at the value they reached
+/// and `watch` and `interactive` restore the terminal before exiting.
+pub fn install_interrupt_handler() {
+    if let Err(err) = ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst)) {
+        warn!("Failed to install Ctrl-C handler, Ctrl-C will stop monb abruptly ({})", err);
+    }
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::feature::Feature;
+
+pub const STATE_FILE_ENV: &'static str = "MONB_STATE_FILE";
+const STATE_DIRECTORY: &'static str = "monb";
+const STATE_FILE: &'static str = "state.json";
+
+// fades set monitors from parallel threads, every read-modify-write of the file holds this lock
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+// all changes made by one invocation share a number, `undo` reverts only the newest one
+static CHANGE: OnceLock<u64> = OnceLock::new();
+
+/// Value of a monitor before and after its last change, on the `0..=max` scale it was set on.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub previous: u32,
+    pub current: u32,
+    pub max: u32,
+    pub change: u64
+}
+
+impl Entry {
+    /// Converts a saved value to a monitor scale, values saved with `--raw` can be restored as percentages and vice versa.
+    pub fn rescale(&self, value: u32, max: u32, feature: Feature) -> u32 {
+        if self.max == max || self.max == 0 || !feature.is_continuous() {
+            return value;
+        }
+
+        return ((value as f64 * max as f64 / self.max as f64).round() as u32).min(max);
+    }
+
+    /// Value `undo` goes back to, or `restore` sets again, on the `0..=max` scale of a monitor.
+    pub fn restored(&self, undo: bool, max: u32, feature: Feature) -> u32 {
+        self.rescale(crate::ternary_operator!(undo, self.previous, self.current), max, feature)
+    }
+}
+
+/// Last changes keyed by monitor identity and feature, so they survive monitor reordering.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    last_change: u64,
+    monitors: BTreeMap<String, BTreeMap<String, Entry>>
+}
+
+impl State {
+    /// `$XDG_STATE_HOME/monb/state.json` (local data directory on other platforms), unless `MONB_STATE_FILE` says otherwise.
+    pub fn path() -> Option<PathBuf> {
+        // tests change monitors through the same path as the command line, they must never touch the real state
+        if cfg!(test) {
+            return Some(std::env::temp_dir().join(format!("monb-test-{}", std::process::id())).join(STATE_FILE));
+        }
+
+        if let Some(path) = std::env::var_os(STATE_FILE_ENV) {
+            return Some(PathBuf::from(path));
+        }
+
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|directory| directory.join(STATE_DIRECTORY).join(STATE_FILE))
+    }
+
+    pub fn load() -> Result<Self, String> {
+        let path: PathBuf = Self::path().ok_or("No state directory, can't save brightness state")?;
+
+        let content: String = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                debug!("State file `{}` not found, nothing saved yet", path.display());
+                return Ok(Self::default());
+            },
+            Err(err) => return Err(format!("Failed to read state file `{}` ({})", path.display(), err))
+        };
+
+        return serde_json::from_str(&content).map_err(|err| format!("Invalid state file `{}` ({})", path.display(), err));
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path: PathBuf = Self::path().ok_or("No state directory, can't save brightness state")?;
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|err| format!("Failed to create state directory `{}` ({})", directory.display(), err))?;
+        }
+
+        let content: String = serde_json::to_string_pretty(self).map_err(|err| format!("Failed to serialize state ({})", err))?;
+
+        // written next to the file and renamed, an interrupted write can't leave a half written state
+        let temporary: PathBuf = path.with_extension("json.tmp");
+        fs::write(&temporary, content).map_err(|err| format!("Failed to write state file `{}` ({})", temporary.display(), err))?;
+        fs::rename(&temporary, &path).map_err(|err| format!("Failed to write state file `{}` ({})", path.display(), err))?;
+
+        return Ok(());
+    }
+
+    /// Newest entry of a monitor, `keys` are tried from the most to the least specific, see `Monitor::keys`.
+    pub fn entry(&self, keys: &[String], feature: Feature) -> Option<Entry> {
+        let feature: &'static str = feature.into();
+        keys.iter().find_map(|key| self.monitors.get(key)?.get(feature).copied())
+    }
+
+    /// Entries of monitors given by their `keys` and feature, in the same order. `undo` keeps only entries of the newest change among them.
+    pub fn entries(&self, monitors: &[(Vec<String>, Feature)], undo: bool) -> Vec<Option<Entry>> {
+        let entries: Vec<Option<Entry>> = monitors.iter().map(|(keys, feature)| self.entry(keys, *feature)).collect();
+        if !undo {
+            return entries;
+        }
+
+        let last_change: Option<u64> = entries.iter().flatten().map(|entry| entry.change).max();
+        return entries.into_iter().map(|entry| entry.filter(|entry| Some(entry.change) == last_change)).collect();
+    }
+
+    /// Saves a change of a monitor under its most specific key, entries under its other keys are dropped.
+    /// A monitor changed several times by one invocation keeps the value from before the first change.
+    pub fn record(keys: &[String], feature: Feature, previous: u32, current: u32, max: u32) -> Result<(), String> {
+        let _lock = STATE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut state: State = Self::load()?;
+
+        let change: u64 = *CHANGE.get_or_init(|| state.last_change + 1);
+        state.apply(keys, feature, previous, current, max, change)?;
+
+        return state.save();
+    }
+
+    /// Same as `record`, in memory and with the number of the change given.
+    pub fn apply(&mut self, keys: &[String], feature: Feature, previous: u32, current: u32, max: u32, change: u64) -> Result<(), String> {
+        let primary_key: &String = keys.first().ok_or("Monitor has no identifiers")?;
+        self.last_change = self.last_change.max(change);
+
+        // e.g. every key press of the interactive mode, `undo` reverts all of them at once
+        let previous: u32 = match self.entry(keys, feature) {
+            Some(entry) if entry.change == change => entry.previous,
+            _ => previous
+        };
+        let feature: &'static str = feature.into();
+
+        for key in &keys[1..] {
+            if let Some(features) = self.monitors.get_mut(key) {
+                features.remove(feature);
+                if features.is_empty() {
+                    self.monitors.remove(key);
+                }
+            }
+        }
+
+        self.monitors.entry(primary_key.clone())
+            .or_default()
+            .insert(feature.to_string(), Entry { previous, current, max, change });
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|key| key.to_string()).collect()
+    }
+
+    fn monitors(ids: &[&str]) -> Vec<(Vec<String>, Feature)> {
+        ids.iter().map(|id| (keys(&[id]), Feature::Brightness)).collect()
+    }
+
+    // `DP-1` changed from 40 to 60, then `DP-2` from 20 to 30 by a later invocation
+    fn two_changes() -> State {
+        let mut state: State = State::default();
+        state.apply(&keys(&["DP-1"]), Feature::Brightness, 40, 60, 100, 1).unwrap();
+        state.apply(&keys(&["DP-2"]), Feature::Brightness, 20, 30, 100, 2).unwrap();
+        return state;
+    }
+
+    fn restored(state: &State, ids: &[&str], undo: bool) -> Vec<Option<u32>> {
+        state.entries(&monitors(ids), undo).into_iter()
+            .map(|entry| entry.map(|entry| entry.restored(undo, 100, Feature::Brightness)))
+            .collect()
+    }
+
+    #[test]
+    fn undo_reverts_only_the_newest_change() {
+        assert_eq!(restored(&two_changes(), &["DP-1", "DP-2"], true), vec![None, Some(20)]);
+    }
+
+    #[test]
+    fn restore_sets_every_saved_value_again() {
+        assert_eq!(restored(&two_changes(), &["DP-1", "DP-2"], false), vec![Some(60), Some(30)]);
+    }
+
+    #[test]
+    fn undo_only_looks_at_connected_monitors() {
+        assert_eq!(restored(&two_changes(), &["DP-1", "HDMI-1"], true), vec![Some(40), None]);
+    }
+
+    #[test]
+    fn changes_of_one_invocation_are_undone_at_once() {
+        let mut state: State = two_changes();
+        state.apply(&keys(&["DP-1"]), Feature::Brightness, 60, 70, 100, 3).unwrap();
+        state.apply(&keys(&["DP-1"]), Feature::Brightness, 70, 80, 100, 3).unwrap();
+
+        assert_eq!(state.entry(&keys(&["DP-1"]), Feature::Brightness), Some(Entry { previous: 60, current: 80, max: 100, change: 3 }));
+        assert_eq!(state.last_change, 3);
+    }
+
+    #[test]
+    fn raw_entries_are_rescaled_for_percentages() {
+        let mut state: State = State::default();
+        state.apply(&keys(&["eDP-1"]), Feature::Brightness, 9697, 19393, 19393, 1).unwrap();
+
+        assert_eq!(restored(&state, &["eDP-1"], true), vec![Some(50)]);
+        assert_eq!(restored(&state, &["eDP-1"], false), vec![Some(100)]);
+    }
+
+    #[test]
+    fn codes_are_never_rescaled() {
+        let entry: Entry = Entry { previous: 15, current: 17, max: 255, change: 1 };
+
+        assert_eq!(entry.restored(true, 100, Feature::InputSource), 15);
+        assert_eq!(entry.restored(false, 100, Feature::InputSource), 17);
+    }
+
+    #[test]
+    fn entries_move_to_the_most_specific_key() {
+        let mut state: State = State::default();
+        state.apply(&keys(&["DP-2"]), Feature::Brightness, 20, 30, 100, 1).unwrap();
+        state.apply(&keys(&["@ABC123", "DP-2"]), Feature::Brightness, 30, 50, 100, 2).unwrap();
+
+        assert_eq!(state.entry(&keys(&["DP-2"]), Feature::Brightness), None);
+        assert_eq!(state.entry(&keys(&["@ABC123", "DP-2"]), Feature::Brightness).map(|entry| entry.previous), Some(30));
+    }
+
+    #[test]
+    fn features_are_saved_separately() {
+        let mut state: State = two_changes();
+        state.apply(&keys(&["DP-1"]), Feature::Contrast, 50, 75, 100, 3).unwrap();
+
+        assert_eq!(state.entry(&keys(&["DP-1"]), Feature::Brightness).map(|entry| entry.current), Some(60));
+        assert_eq!(state.entry(&keys(&["DP-1"]), Feature::Contrast).map(|entry| entry.current), Some(75));
+    }
+
+    #[test]
+    fn monitors_without_identifiers_are_not_saved() {
+        assert_eq!(State::default().apply(&[], Feature::Brightness, 20, 30, 100, 1), Err("Monitor has no identifiers".into()));
+    }
+}
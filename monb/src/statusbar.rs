@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use clap::ArgMatches;
+use log::{debug, error};
+use serde::Serialize;
+use strum::{Display, EnumIter, IntoStaticStr};
+
+use crate::backend::BrightnessBackend;
+use crate::cli::params::{Arguments, BAction, BArg, BScope, BValue, ARG_BAR_ID, ARG_BAR_SCOPE_ID, ARG_OUTPUT_LONG_NAME, ARG_SCROLL_ID, ARG_STEP_ID, FLAG_FOLLOW_ID, SUBCOMMAND_BAR_NAME};
+use crate::monitor::{apply_setter_barg, get_all_devices, read_values, resolve_scope, Monitor};
+use crate::template::TemplateValues;
+use crate::progressbar::create_progressbar;
+
+// i3blocks runs the block again with the clicked button, 4 and 5 are the scroll wheel
+const BLOCK_BUTTON_ENV: &'static str = "BLOCK_BUTTON";
+const BLOCK_BUTTON_SCROLL_UP: &'static str = "4";
+const BLOCK_BUTTON_SCROLL_DOWN: &'static str = "5";
+
+pub const STEP_DEFAULT: usize = 5;
+
+// DDC/CI reads are slow, polling more often only keeps the bus busy
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Status bar the output is formatted for, selected with `monb bar <BAR>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, IntoStaticStr, Display)]
+pub enum StatusBar {
+    #[strum(serialize = "waybar")]      Waybar,
+    #[strum(serialize = "i3blocks")]    I3blocks,
+    #[strum(serialize = "polybar")]     Polybar
+}
+
+impl TryFrom<Option<&String>> for StatusBar {
+    type Error = String;
+
+    fn try_from(value: Option<&String>) -> Result<Self, Self::Error> {
+        let potential_bar: &String = match value {
+            None => return Err("Status bar is missing".into()),
+            Some(v) => v
+        };
+
+        debug!("Parsing status bar: `{}`", potential_bar);
+
+        match potential_bar.trim().to_lowercase().as_str() {
+            "waybar" => Ok(Self::Waybar),
+            "i3blocks" | "i3" => Ok(Self::I3blocks),
+            "polybar" => Ok(Self::Polybar),
+            _ => Err(format!("Invalid status bar: `{}`", potential_bar))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scroll {
+    Up,
+    Down
+}
+
+impl FromStr for Scroll {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "up" => Ok(Self::Up),
+            "down" => Ok(Self::Down),
+            _ => Err(format!("Invalid scroll direction: `{}`, expected `up` or `down`", s))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StatusBarInfo {
+    pub bar: StatusBar,
+    pub scope: Option<String>,
+    pub scroll: Option<Scroll>,
+    pub step: usize,
+    pub follow: bool
+}
+
+impl TryFrom<&ArgMatches> for StatusBarInfo {
+    type Error = String;
+
+    fn try_from(value: &ArgMatches) -> Result<Self, Self::Error> {
+        let bar: StatusBar = StatusBar::try_from(value.get_one::<String>(ARG_BAR_ID))?;
+
+        // `--scroll` wins over the button i3blocks passes
+        let scroll: Option<Scroll> = match value.get_one::<String>(ARG_SCROLL_ID) {
+            Some(s) => Some(Scroll::from_str(s)?),
+            None if bar == StatusBar::I3blocks => match std::env::var(BLOCK_BUTTON_ENV).as_deref() {
+                Ok(BLOCK_BUTTON_SCROLL_UP) => Some(Scroll::Up),
+                Ok(BLOCK_BUTTON_SCROLL_DOWN) => Some(Scroll::Down),
+                _ => None
+            },
+            None => None
+        };
+
+        let step: usize = match value.get_one::<String>(ARG_STEP_ID) {
+            None => STEP_DEFAULT,
+            Some(s) => match s.trim().parse::<usize>() {
+                Ok(step) if step > 0 => step,
+                _ => return Err(format!("Scroll step `{}` is not a positive integer", s))
+            }
+        };
+
+        Ok(StatusBarInfo {
+            bar,
+            scope: value.get_one::<String>(ARG_BAR_SCOPE_ID).cloned(),
+            scroll,
+            step,
+            follow: value.get_flag(FLAG_FOLLOW_ID)
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct WaybarOutput {
+    text: String,
+    tooltip: String,
+    percentage: u32,
+    class: &'static str
+}
+
+#[derive(Serialize)]
+struct I3blocksOutput {
+    full_text: String,
+    short_text: String
+}
+
+// css class for waybar, e.g. to pick a different icon
+fn level_class(percentage: u32) -> &'static str {
+    match percentage {
+        0 => "off",
+        1..=33 => "low",
+        34..=66 => "medium",
+        _ => "high"
+    }
+}
+
+fn render(info: &StatusBarInfo, arguments: &Arguments, devices: &HashMap<usize, Monitor>, readings: &[(usize, Result<u32, String>)]) -> Result<String, String> {
+    let mut texts: Vec<String> = Vec::<String>::new();
+    let mut tooltips: Vec<String> = Vec::<String>::new();
+    let mut percentages: Vec<u32> = Vec::<u32>::new();
+
+    for (index, reading) in readings {
+        let monitor: &Monitor = &devices[index];
+        let value: u32 = match reading {
+            Ok(v) => *v,
+            Err(err) => {
+                error!("{} - {}", monitor.name, err);
+                tooltips.push(format!("{}: unavailable", monitor.name));
+                continue;
+            }
+        };
+
+        let percentage: u32 = monitor.percentage(value, monitor.max());
+        texts.push(match &arguments.template {
+            None => format!("{}%", percentage),
+            Some(t) => t.render(&TemplateValues {
+                index: *index,
+                name: &monitor.name,
+                id: &monitor.id,
+                value,
+                percent: percentage,
+                bar: &create_progressbar(percentage, &arguments.progressbar_info)
+            })
+        });
+        tooltips.push(format!("{}: {}%", monitor.name, percentage));
+        percentages.push(percentage);
+    }
+
+    // several monitors are shown side by side, the short text and the percentage are their average
+    let average: Option<u32> = match percentages.len() {
+        0 => None,
+        count => Some((percentages.iter().sum::<u32>() as f64 / count as f64).round() as u32)
+    };
+    let text: String = crate::ternary_operator!(texts.is_empty(), "N/A".into(), texts.join(" "));
+    let short_text: String = average.map_or("N/A".into(), |a| format!("{}%", a));
+
+    return match info.bar {
+        StatusBar::Waybar => serde_json::to_string(&WaybarOutput {
+            text,
+            tooltip: tooltips.join("\n"),
+            percentage: average.unwrap_or(0),
+            class: average.map_or("unavailable", level_class)
+        }).map_err(|err| format!("Failed to serialize waybar output ({})", err)),
+
+        StatusBar::I3blocks => serde_json::to_string(&I3blocksOutput { full_text: text, short_text })
+            .map_err(|err| format!("Failed to serialize i3blocks output ({})", err)),
+
+        // polybar shows the line as it is
+        StatusBar::Polybar => Ok(text)
+    };
+}
+
+/// Prints the selected monitors in the format of a status bar, after applying a scroll step.
+/// With `--follow` a new line is printed whenever a value changes, until the bar closes stdout.
+pub fn run_status_bar(arguments: Arguments, info: StatusBarInfo) -> Result<(), String> {
+    // records would be mixed into the lines read by the status bar
+    if arguments.output.is_structured() {
+        return Err(format!("`{}` can't be combined with `--{} {}`", SUBCOMMAND_BAR_NAME, ARG_OUTPUT_LONG_NAME, arguments.output));
+    }
+
+    let scope: BScope = match &info.scope {
+        None => BScope::Global,
+        Some(s) => BScope::from_str(s)?.expand_aliases(&arguments.config)
+    };
+
+    let backend: Box<dyn BrightnessBackend> = arguments.backend.create(arguments.flag_raw)?;
+    let devices: HashMap<usize, Monitor> = get_all_devices(backend.as_ref(), &arguments)?;
+    let indices: Vec<usize> = resolve_scope(&devices, &scope);
+
+    if let Some(scroll) = info.scroll {
+        let action: BAction = crate::ternary_operator!(scroll == Scroll::Up, BAction::Inc, BAction::Dec);
+        let barg: BArg = BArg::new(scope.clone(), BValue::new(action, info.step.min(arguments.value_max)));
+
+        for index in &indices {
+            if let Err(err) = apply_setter_barg(&devices[index], &barg, None) {
+                error!("{}", err);
+            }
+        }
+    }
+
+    let mut stdout = std::io::stdout();
+    let mut last_readings: Option<Vec<(usize, Result<u32, String>)>> = None;
+    loop {
+        let readings: Vec<(usize, Result<u32, String>)> = read_values(&devices, &indices);
+        if last_readings.as_ref() != Some(&readings) {
+            let line: String = render(&info, &arguments, &devices, &readings)?;
+
+            // bars close the pipe when they exit or reload
+            if writeln!(stdout, "{}", line).and_then(|_| stdout.flush()).is_err() {
+                debug!("Status bar closed stdout, exiting");
+                return Ok(());
+            }
+            last_readings = Some(readings);
+        }
+
+        if !info.follow {
+            return Ok(());
+        }
+        thread::sleep(FOLLOW_INTERVAL);
+    }
+}
@@ -0,0 +1,139 @@
+use std::str::FromStr;
+
+use strum::{Display, EnumIter, IntoEnumIterator, IntoStaticStr};
+
+/// Value a template placeholder is replaced with, `{name}` in the template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumIter, IntoStaticStr, Display)]
+pub enum Placeholder {
+    #[strum(serialize = "index")]   Index,
+    #[strum(serialize = "name")]    Name,
+    #[strum(serialize = "id")]      Id,
+    #[strum(serialize = "value")]   Value,
+    #[strum(serialize = "percent")] Percent,
+    #[strum(serialize = "bar")]     Bar
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Placeholder(Placeholder)
+}
+
+/// Getter line template set with `--format`, e.g. `{name}: {percent}% {bar}`.
+/// `{{` and `}}` are literal braces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>
+}
+
+/// Values of one monitor, `bar` is already rendered with the selected progressbar style.
+pub struct TemplateValues<'a> {
+    pub index: usize,
+    pub name: &'a str,
+    pub id: &'a str,
+
+    // device value (the raw one with `--raw`) and the percentage shown by the progressbar
+    pub value: u32,
+    pub percent: u32,
+    pub bar: &'a str
+}
+
+fn possible_placeholders() -> String {
+    Placeholder::iter().map(|placeholder| format!("`{{{}}}`", placeholder)).collect::<Vec<String>>().join(", ")
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments: Vec<Segment> = Vec::<Segment>::new();
+        let mut text: String = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => { chars.next(); text.push('{'); },
+                '}' if chars.peek() == Some(&'}') => { chars.next(); text.push('}'); },
+                '}' => return Err(format!("Format `{}` has an unmatched `}}`, use `}}}}` for a literal brace", s)),
+                '{' => {
+                    let mut name: String = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(n) => name.push(n),
+                            None => return Err(format!("Format `{}` has an unclosed placeholder `{{{}`, use `{{{{` for a literal brace", s, name))
+                        }
+                    }
+
+                    let placeholder: Placeholder = Placeholder::iter()
+                        .find(|placeholder| name.trim().eq_ignore_ascii_case(placeholder.into()))
+                        .ok_or(format!("Format `{}` has an unknown placeholder `{{{}}}`. Possible placeholders: {}", s, name, possible_placeholders()))?;
+
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Placeholder(placeholder));
+                },
+                c => text.push(c)
+            }
+        }
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        return Ok(Self { segments });
+    }
+}
+
+impl Template {
+    pub fn render(&self, values: &TemplateValues) -> String {
+        self.segments.iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                Segment::Placeholder(Placeholder::Index) => values.index.to_string(),
+                Segment::Placeholder(Placeholder::Name) => values.name.to_string(),
+                Segment::Placeholder(Placeholder::Id) => values.id.to_string(),
+                Segment::Placeholder(Placeholder::Value) => values.value.to_string(),
+                Segment::Placeholder(Placeholder::Percent) => values.percent.to_string(),
+                Segment::Placeholder(Placeholder::Bar) => values.bar.to_string()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, value: u32, percent: u32) -> String {
+        let values: TemplateValues = TemplateValues { index: 2, name: "Dell", id: "DP-1", value, percent, bar: "[==]" };
+        return Template::from_str(template).unwrap().render(&values);
+    }
+
+    #[test]
+    fn value_is_the_device_value_and_percent_the_percentage() {
+        assert_eq!(render("{name}: {percent}% ({value}) {bar}", 19393, 20), "Dell: 20% (19393) [==]");
+        assert_eq!(render("{index} {id} {{{value}}}", 50, 50), "2 DP-1 {50}");
+    }
+
+    #[test]
+    fn placeholders_are_case_insensitive() {
+        assert_eq!(render("{ Percent }%", 19393, 20), "20%");
+    }
+
+    #[test]
+    fn unknown_placeholders_are_rejected() {
+        assert_eq!(
+            Template::from_str("{brightness}"),
+            Err("Format `{brightness}` has an unknown placeholder `{brightness}`. Possible placeholders: `{index}`, `{name}`, `{id}`, `{value}`, `{percent}`, `{bar}`".into())
+        );
+    }
+
+    #[test]
+    fn unmatched_braces_are_rejected() {
+        assert_eq!(Template::from_str("{name"), Err("Format `{name` has an unclosed placeholder `{name`, use `{{` for a literal brace".into()));
+        assert_eq!(Template::from_str("name}"), Err("Format `name}` has an unmatched `}`, use `}}` for a literal brace".into()));
+    }
+}
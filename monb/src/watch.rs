@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::io::{IsTerminal, Stdout, Write};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::ArgMatches;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::style::{Print, PrintStyledContent, Stylize};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use log::{debug, LevelFilter};
+
+use crate::backend::BrightnessBackend;
+use crate::cli::params::{Arguments, BScope, ARG_INTERVAL_ID, ARG_WATCH_SCOPE_ID};
+use crate::fade;
+use crate::signal;
+use crate::monitor::{get_all_devices, read_values, resolve_scope, Monitor};
+
+pub const INTERVAL_DEFAULT: Duration = Duration::from_secs(1);
+
+// how often Ctrl-C and terminal resizes are checked between two readings
+const TICK: Duration = Duration::from_millis(50);
+
+#[derive(Debug)]
+pub struct WatchInfo {
+    pub scope: Option<String>,
+    pub interval: Duration
+}
+
+impl TryFrom<&ArgMatches> for WatchInfo {
+    type Error = String;
+
+    fn try_from(value: &ArgMatches) -> Result<Self, Self::Error> {
+        let interval: Duration = match value.get_one::<String>(ARG_INTERVAL_ID) {
+            None => INTERVAL_DEFAULT,
+            Some(i) => match fade::parse_duration(i, "Watch interval")? {
+                d if d.is_zero() => return Err(format!("Watch interval `{}` has to be positive", i)),
+                d => d
+            }
+        };
+
+        Ok(WatchInfo {
+            scope: value.get_one::<String>(ARG_WATCH_SCOPE_ID).cloned(),
+            interval
+        })
+    }
+}
+
+/// Alternate screen with a hidden cursor and silenced logs, the terminal is restored when it's dropped,
+/// on errors and panics too. Raw mode passes every key press to the program, including Ctrl-C.
+pub struct Screen {
+    stdout: Stdout,
+    log_level: LevelFilter,
+    raw: bool
+}
+
+impl Screen {
+    pub fn enter(raw: bool) -> Result<Self, String> {
+        let mut stdout: Stdout = std::io::stdout();
+        if !stdout.is_terminal() {
+            return Err("Standard output is not a terminal".into());
+        }
+
+        execute!(stdout, EnterAlternateScreen, Hide).map_err(|err| format!("Failed to prepare the terminal ({})", err))?;
+
+        // log lines would be drawn over the frame
+        let log_level: LevelFilter = log::max_level();
+        log::set_max_level(LevelFilter::Off);
+
+        // created before raw mode, so dropping it leaves the alternate screen even if raw mode fails
+        let mut screen: Screen = Self { stdout, log_level, raw: false };
+        if raw {
+            terminal::enable_raw_mode().map_err(|err| format!("Failed to prepare the terminal ({})", err))?;
+            screen.raw = true;
+        }
+
+        return Ok(screen);
+    }
+
+    /// Draws the lines from the top left corner, highlighted lines are bold and yellow (only bold with `--nocolor`).
+    /// Lines are cut at the terminal width, so they never wrap and move the lines below.
+    pub fn draw(&mut self, lines: &[(String, bool)], clear: bool) -> std::io::Result<()> {
+        let (columns, rows) = terminal::size()?;
+        if clear {
+            queue!(self.stdout, Clear(ClearType::All))?;
+        }
+
+        for (row, (line, highlighted)) in lines.iter().take(rows as usize).enumerate() {
+            let line: String = line.chars().take(columns as usize).collect();
+            queue!(self.stdout, MoveTo(0, row as u16))?;
+            match highlighted {
+                true => queue!(self.stdout, PrintStyledContent(line.bold().yellow()))?,
+                false => queue!(self.stdout, Print(line))?
+            };
+            queue!(self.stdout, Clear(ClearType::UntilNewLine))?;
+        }
+
+        if lines.len() < rows as usize {
+            queue!(self.stdout, MoveTo(0, lines.len() as u16), Clear(ClearType::FromCursorDown))?;
+        }
+
+        return self.stdout.flush();
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        log::set_max_level(self.log_level);
+        if self.raw {
+            if let Err(err) = terminal::disable_raw_mode() {
+                debug!("Failed to disable raw mode ({})", err);
+            }
+        }
+        if let Err(err) = execute!(self.stdout, Show, LeaveAlternateScreen) {
+            debug!("Failed to restore the terminal ({})", err);
+        }
+    }
+}
+
+fn frame(arguments: &Arguments, info: &WatchInfo, devices: &HashMap<usize, Monitor>, readings: &[(usize, Result<u32, String>)], changed: &[usize]) -> Vec<(String, bool)> {
+    let mut lines: Vec<(String, bool)> = vec![
+        (format!("monb watch - every {:?}, Ctrl-C to exit", info.interval), false),
+        (String::new(), false)
+    ];
+
+    for (index, reading) in readings {
+        let monitor: &Monitor = &devices[index];
+        let line: String = match reading {
+            Ok(value) => monitor.line(*value, monitor.max(), &arguments.progressbar_info, arguments.template.as_ref()),
+            Err(err) => format!("{} - unavailable ({})", monitor.name, err)
+        };
+        lines.push((line, changed.contains(index)));
+    }
+
+    return lines;
+}
+
+/// Redraws progressbars of the selected monitors in place every interval, until Ctrl-C.
+pub fn watch(arguments: Arguments, info: WatchInfo) -> Result<(), String> {
+    let scope: BScope = match &info.scope {
+        None => BScope::Global,
+        Some(s) => BScope::from_str(s)?.expand_aliases(&arguments.config)
+    };
+
+    let backend: Box<dyn BrightnessBackend> = arguments.backend.create(arguments.flag_raw)?;
+    let devices: HashMap<usize, Monitor> = get_all_devices(backend.as_ref(), &arguments)?;
+    let indices: Vec<usize> = resolve_scope(&devices, &scope);
+    if indices.is_empty() {
+        return Ok(()); // already reported, there is nothing to watch
+    }
+
+    signal::install_interrupt_handler();
+    let mut screen: Screen = Screen::enter(false)?;
+
+    let mut last_readings: Option<Vec<(usize, Result<u32, String>)>> = None;
+    let mut lines: Vec<(String, bool)> = Vec::<(String, bool)>::new();
+    let mut size: Option<(u16, u16)> = None;
+    let mut next_reading: Instant = Instant::now();
+
+    while !signal::is_interrupted() {
+        let now: Instant = Instant::now();
+        let reading_due: bool = now >= next_reading;
+        if reading_due {
+            let readings: Vec<(usize, Result<u32, String>)> = read_values(&devices, &indices);
+            let changed: Vec<usize> = match &last_readings {
+                None => vec![],
+                Some(last) => readings.iter().zip(last).filter(|(new, old)| new != old).map(|(new, _)| new.0).collect()
+            };
+
+            lines = frame(&arguments, &info, &devices, &readings, &changed);
+            last_readings = Some(readings);
+
+            // slow readings (DDC/CI) skip frames instead of piling up
+            next_reading += info.interval;
+            if next_reading < now {
+                next_reading = now + info.interval;
+            }
+        }
+
+        // a resized terminal is redrawn right away from the last frame
+        let current_size: Option<(u16, u16)> = terminal::size().ok();
+        let resized: bool = size.is_some() && current_size != size;
+        if reading_due || resized {
+            screen.draw(&lines, resized || size.is_none()).map_err(|err| format!("Failed to draw ({})", err))?;
+            size = current_size;
+        }
+
+        thread::sleep(TICK);
+    }
+
+    return Ok(());
+}